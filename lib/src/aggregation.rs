@@ -0,0 +1,154 @@
+use std::collections::BTreeMap;
+
+use crate::collection::Collection;
+use crate::field::{Field, FieldValue};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Aggregation {
+    /// Min, max, average and sum of an int field.
+    Stats(String),
+    /// Number of documents per fixed-size interval of an int field.
+    Histogram(String, i64),
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Stats {
+    pub count: usize,
+    pub min: Option<i64>,
+    pub max: Option<i64>,
+    pub avg: Option<f64>,
+    /// Exact sum, it doesn't fit into `i64` for large values.
+    pub sum: i128,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bucket {
+    /// Lower bound of the interval, `i64::MIN` for the lowest interval which starts below it.
+    pub key: i64,
+    pub count: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AggregationResult {
+    Stats(Stats),
+    /// Non-empty buckets ordered by key.
+    Histogram(Vec<Bucket>),
+}
+
+impl Aggregation {
+    pub fn field_name(&self) -> &String {
+        match self {
+            Aggregation::Stats(name) => name,
+            Aggregation::Histogram(name, _) => name,
+        }
+    }
+
+    pub fn aggregate<'a>(
+        &self,
+        collection: &Collection,
+        field: &Field,
+        ids: impl Iterator<Item = &'a i32>,
+    ) -> AggregationResult {
+        let values = ids.filter_map(|id| match collection.get(*id)?.get(field.name.as_str()) {
+            Some(FieldValue::Int(value)) => Some(*value),
+            _ => None,
+        });
+
+        match self {
+            Aggregation::Stats(_) => {
+                let mut stats = Stats::default();
+
+                for value in values {
+                    stats.count += 1;
+                    stats.sum += value as i128;
+                    stats.min = Some(stats.min.map_or(value, |x| x.min(value)));
+                    stats.max = Some(stats.max.map_or(value, |x| x.max(value)));
+                }
+
+                if stats.count > 0 {
+                    stats.avg = Some(stats.sum as f64 / stats.count as f64);
+                }
+
+                AggregationResult::Stats(stats)
+            }
+            Aggregation::Histogram(_, interval) => {
+                let mut buckets: BTreeMap<i64, usize> = BTreeMap::new();
+
+                for value in values {
+                    let key = (value as i128).div_euclid(*interval as i128) * *interval as i128;
+                    *buckets.entry(i64::try_from(key).unwrap_or(i64::MIN)).or_default() += 1;
+                }
+
+                AggregationResult::Histogram(
+                    buckets
+                        .into_iter()
+                        .map(|(key, count)| Bucket { key, count })
+                        .collect(),
+                )
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::Document;
+
+    fn aggregate(aggregation: Aggregation, values: &[i64]) -> AggregationResult {
+        let mut collection = Collection::new();
+        collection.push_field("price", FieldValue::Int(0));
+
+        for value in values {
+            let mut doc = Document::new();
+            doc.push("price", FieldValue::Int(*value));
+            collection.push(doc, None);
+        }
+
+        collection.commit();
+        aggregation.aggregate(&collection, &collection.fields[0], collection.iter().map(|x| x.0))
+    }
+
+    fn buckets(values: &[i64], interval: i64) -> Vec<(i64, usize)> {
+        match aggregate(Aggregation::Histogram("price".into(), interval), values) {
+            AggregationResult::Histogram(buckets) => buckets.into_iter().map(|x| (x.key, x.count)).collect(),
+            result => panic!("{:?}", result),
+        }
+    }
+
+    #[test]
+    fn test_stats() {
+        let stats = Stats {
+            count: 4,
+            min: Some(-2),
+            max: Some(7),
+            avg: Some(2.5),
+            sum: 10,
+        };
+
+        assert_eq!(aggregate(Aggregation::Stats("price".into()), &[1, -2, 7, 4]), AggregationResult::Stats(stats));
+        assert_eq!(aggregate(Aggregation::Stats("price".into()), &[]), AggregationResult::Stats(Stats::default()));
+    }
+
+    #[test]
+    fn test_stats_extreme() {
+        let result = aggregate(Aggregation::Stats("price".into()), &[i64::MAX, i64::MAX]);
+
+        match result {
+            AggregationResult::Stats(stats) => {
+                assert_eq!(stats.sum, i64::MAX as i128 * 2);
+                assert_eq!(stats.avg, Some(i64::MAX as f64));
+            }
+            result => panic!("{:?}", result),
+        }
+    }
+
+    #[test]
+    fn test_histogram() {
+        assert_eq!(buckets(&[1, 5, 12, -3, 10], 10), vec![(-10, 1), (0, 2), (10, 2)]);
+        assert_eq!(
+            buckets(&[i64::MIN, i64::MAX, i64::MIN + 5], 10),
+            vec![(i64::MIN, 2), (i64::MAX - 7, 1)]
+        );
+    }
+}
@@ -0,0 +1,327 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, RwLock};
+
+use bincode::{Decode, Encode};
+use lazy_static::lazy_static;
+
+use crate::char_filter::{CharFilter, FilteredText, HtmlStripFilter};
+use crate::language::Language;
+use crate::token::Token;
+use crate::stop_words::stop_words;
+use crate::token_filter::{
+    AsciiFoldingFilter, LowercaseFilter, NGramFilter, NormalizationFilter, StemmerFilter, StopWordFilter, TokenFilter,
+};
+use crate::tokenizer::{CjkTokenizer, Tokenizer, WordTokenizer};
+
+/// Builds the analyzer of a field from its configuration, for indexing or querying.
+pub type AnalyzerBuilder = Arc<dyn Fn(&AnalyzerConfig, AnalysisMode) -> Box<dyn Analyzer> + Send + Sync>;
+
+lazy_static! {
+    static ref STANDARD: TextAnalyzer = standard_analyzer(&AnalyzerConfig::default());
+    static ref ANALYZERS: RwLock<HashMap<String, AnalyzerBuilder>> = {
+        let mut analyzers: HashMap<String, AnalyzerBuilder> = HashMap::new();
+        analyzers.insert("standard".into(), Arc::new(|config, _| Box::new(standard_analyzer(config))));
+        analyzers.insert("cjk".into(), Arc::new(|config, _| Box::new(cjk_analyzer(config))));
+        analyzers.insert("ngram".into(), Arc::new(|config, mode| Box::new(ngram_analyzer(config, mode, false))));
+        analyzers.insert("edge_ngram".into(), Arc::new(|config, mode| Box::new(ngram_analyzer(config, mode, true))));
+        RwLock::new(analyzers)
+    };
+}
+
+/// Turns text into the tokens which are stored in the index and looked up by queries.
+pub trait Analyzer: Send + Sync {
+    fn analyze(&self, text: &str) -> Vec<Token>;
+}
+
+/// Character filters, then a tokenizer followed by a chain of token filters.
+pub struct TextAnalyzer {
+    char_filters: Vec<Box<dyn CharFilter>>,
+    tokenizer: Box<dyn Tokenizer>,
+    filters: Vec<Box<dyn TokenFilter>>,
+}
+
+impl TextAnalyzer {
+    pub fn new(tokenizer: impl Tokenizer + 'static) -> Self {
+        TextAnalyzer {
+            char_filters: Vec::new(),
+            tokenizer: Box::new(tokenizer),
+            filters: Vec::new(),
+        }
+    }
+
+    /// Appends the character filter, which runs before the tokenizer.
+    pub fn char_filter(mut self, filter: impl CharFilter + 'static) -> Self {
+        self.char_filters.push(Box::new(filter));
+        self
+    }
+
+    /// Appends the filter to the chain.
+    pub fn filter(mut self, filter: impl TokenFilter + 'static) -> Self {
+        self.filters.push(Box::new(filter));
+        self
+    }
+}
+
+impl Analyzer for TextAnalyzer {
+    fn analyze(&self, text: &str) -> Vec<Token> {
+        let tokens = match self.char_filters.is_empty() {
+            true => self.tokenizer.tokenize(text),
+            false => {
+                let filtered = self
+                    .char_filters
+                    .iter()
+                    .fold(FilteredText::new(text), |text, filter| text.then(filter.as_ref()));
+                let mut tokens = self.tokenizer.tokenize(&filtered.text);
+
+                //offsets of the original text, so highlights keep the markup
+                for token in tokens.iter_mut() {
+                    (token.start, token.end) = filtered.original(token.start, token.end);
+                }
+
+                tokens
+            }
+        };
+
+        self.filters
+            .iter()
+            .fold(tokens, |tokens, filter| filter.filter(tokens))
+    }
+}
+
+/// Keeps the whole text as a single term, as it is.
+pub struct KeywordAnalyzer;
+
+impl Analyzer for KeywordAnalyzer {
+    fn analyze(&self, text: &str) -> Vec<Token> {
+        vec![Token::new(text.into(), 0, 0, text.len())]
+    }
+}
+
+/// What the text is analyzed for, stop words may be kept for indexing and phrases.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnalysisMode {
+    Index,
+    Query,
+    Phrase,
+}
+
+/// Analysis settings of a string field, saved with the schema.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub struct AnalyzerConfig {
+    /// Name of a registered analyzer.
+    pub name: String,
+    /// Language of the stemmer, `None` keeps the words as they are.
+    pub language: Option<Language>,
+    /// Language of the built-in stop words list, `None` keeps all the words.
+    pub stop_words: Option<Language>,
+    /// Stop words of the field in addition to the built-in ones.
+    pub custom_stop_words: Vec<String>,
+    /// Index stop words and keep them in phrase queries, so they only get removed from the other queries.
+    pub keep_stop_words_in_phrases: bool,
+    /// Sizes of the grams of the n-gram analyzers, in characters.
+    pub min_gram: usize,
+    pub max_gram: usize,
+    /// Strip HTML tags and decode entities before tokenizing.
+    pub html: bool,
+}
+
+impl Default for AnalyzerConfig {
+    fn default() -> Self {
+        AnalyzerConfig {
+            name: "standard".into(),
+            language: Some(Language::English),
+            stop_words: None,
+            custom_stop_words: Vec::new(),
+            keep_stop_words_in_phrases: false,
+            min_gram: 2,
+            max_gram: 3,
+            html: false,
+        }
+    }
+}
+
+impl AnalyzerConfig {
+    pub fn new(name: &str) -> Self {
+        AnalyzerConfig {
+            name: name.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Configuration to build the analyzer for the mode from, with the additional stop words.
+    pub fn with_mode(&self, mode: AnalysisMode, stop_words: &[String]) -> AnalyzerConfig {
+        let mut config = self.clone();
+
+        if config.keep_stop_words_in_phrases && mode != AnalysisMode::Query {
+            config.stop_words = None;
+            config.custom_stop_words.clear();
+        } else {
+            config.custom_stop_words.extend_from_slice(stop_words);
+        }
+
+        config
+    }
+
+    /// Built-in and custom stop words, lowercased.
+    pub fn stop_words(&self) -> HashSet<String> {
+        let built_in = self.stop_words.map(stop_words).unwrap_or_default();
+
+        built_in
+            .iter()
+            .map(|x| x.to_string())
+            .chain(self.custom_stop_words.iter().map(|x| x.to_lowercase()))
+            .collect()
+    }
+
+    /// Analyzer registered under the name, `None` if there is no such analyzer.
+    pub fn build(&self, mode: AnalysisMode) -> Option<Box<dyn Analyzer>> {
+        let builder = ANALYZERS.read().unwrap().get(&self.name)?.clone();
+        Some(builder(self, mode))
+    }
+}
+
+/// Splits into Unicode words, normalizes and lowercases them, removes stop words, reduces words to stems of the language
+/// and folds them to ASCII. Diacritics are removed after stemming, as stemmers rely on them.
+pub fn standard_analyzer(config: &AnalyzerConfig) -> TextAnalyzer {
+    word_analyzer(WordTokenizer, config, true)
+}
+
+/// Standard analyzer which splits runs of Chinese, Japanese and Korean characters into overlapping bigrams.
+pub fn cjk_analyzer(config: &AnalyzerConfig) -> TextAnalyzer {
+    word_analyzer(CjkTokenizer, config, true)
+}
+
+/// Optionally strips HTML, normalizes and lowercases the words of the tokenizer, removes stop words, optionally stems them
+/// and folds them to ASCII.
+fn word_analyzer(tokenizer: impl Tokenizer + 'static, config: &AnalyzerConfig, stem: bool) -> TextAnalyzer {
+    let mut analyzer = TextAnalyzer::new(tokenizer);
+
+    if config.html {
+        analyzer = analyzer.char_filter(HtmlStripFilter);
+    }
+
+    analyzer = analyzer
+        .filter(NormalizationFilter)
+        .filter(LowercaseFilter);
+    let stop_words = config.stop_words();
+
+    if !stop_words.is_empty() {
+        analyzer = analyzer.filter(StopWordFilter::new(stop_words));
+    }
+
+    if let Some(language) = config.language.filter(|_| stem) {
+        analyzer = analyzer.filter(StemmerFilter::new(language.algorithm()));
+    }
+
+    analyzer.filter(AsciiFoldingFilter)
+}
+
+/// Splits into Unicode words like the standard analyzer, but indexes the grams of every word along with the word,
+/// so parts of words match too (`phon` matches `smartphone`). Edge grams only start at the beginning of the word.
+/// Words aren't stemmed.
+pub fn ngram_analyzer(config: &AnalyzerConfig, mode: AnalysisMode, edge: bool) -> TextAnalyzer {
+    let filter = NGramFilter {
+        min: config.min_gram,
+        max: config.max_gram,
+        edge,
+        query: mode != AnalysisMode::Index,
+    };
+
+    word_analyzer(WordTokenizer, config, false).filter(filter)
+}
+
+/// Shared instance of the standard analyzer with English stemming.
+pub fn standard() -> &'static TextAnalyzer {
+    &STANDARD
+}
+
+/// Registers an analyzer under the name, so fields can be configured to use it.
+/// The builder gets the configuration of the field, an existing analyzer with the same name is replaced.
+pub fn register<F>(name: &str, builder: F)
+where
+    F: Fn(&AnalyzerConfig, AnalysisMode) -> Box<dyn Analyzer> + Send + Sync + 'static,
+{
+    ANALYZERS.write().unwrap().insert(name.into(), Arc::new(builder));
+}
+
+pub fn is_registered(name: &str) -> bool {
+    ANALYZERS.read().unwrap().contains_key(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct WhitespaceTokenizer;
+
+    impl Tokenizer for WhitespaceTokenizer {
+        fn tokenize(&self, text: &str) -> Vec<Token> {
+            text.split_whitespace()
+                .enumerate()
+                .map(|(position, x)| {
+                    let start = x.as_ptr() as usize - text.as_ptr() as usize;
+                    Token::new(x.into(), position, start, start + x.len())
+                })
+                .collect()
+        }
+    }
+
+    #[test]
+    fn test_language() {
+        let analyze = |language: Option<Language>| -> Vec<String> {
+            let config = AnalyzerConfig {
+                language,
+                ..Default::default()
+            };
+
+            config.build(AnalysisMode::Index).unwrap().analyze("Häuser Katzen").into_iter().map(|x| x.term.value).collect()
+        };
+
+        assert_eq!(analyze(Some(Language::German)), vec!["haus", "katz"]);
+        assert_eq!(analyze(None), vec!["hauser", "katzen"]);
+    }
+
+    #[test]
+    fn test_stop_words() {
+        let config = AnalyzerConfig {
+            stop_words: Some(Language::English),
+            keep_stop_words_in_phrases: true,
+            ..Default::default()
+        };
+        let analyze = |mode| -> Vec<(String, usize)> {
+            let config = config.with_mode(mode, &["bank".into()]);
+            let tokens = config.build(mode).unwrap().analyze("The Bank of America");
+            tokens.into_iter().map(|x| (x.term.value, x.position)).collect()
+        };
+
+        assert_eq!(analyze(AnalysisMode::Query), vec![("america".into(), 3)]);
+        assert_eq!(analyze(AnalysisMode::Phrase).len(), 4);
+    }
+
+    #[test]
+    fn test_register() {
+        register("whitespace", |_, _| Box::new(TextAnalyzer::new(WhitespaceTokenizer).filter(LowercaseFilter)));
+
+        let analyzer = AnalyzerConfig::new("whitespace").build(AnalysisMode::Index).unwrap();
+        let terms: Vec<String> = analyzer.analyze("Dreaming, Rust!").into_iter().map(|x| x.term.value).collect();
+
+        assert_eq!(terms, vec!["dreaming,", "rust!"]);
+        assert!(AnalyzerConfig::new("unknown").build(AnalysisMode::Index).is_none());
+    }
+
+    #[test]
+    fn test_ngram() {
+        let config = AnalyzerConfig::new("ngram");
+        let analyze = |text, mode| -> Vec<String> {
+            let tokens = config.build(mode).unwrap().analyze(text);
+            tokens.into_iter().map(|x| x.term.value).collect()
+        };
+
+        let index = analyze("Phone", AnalysisMode::Index);
+        let query = analyze("phon", AnalysisMode::Query);
+
+        assert_eq!(index, vec!["phone", "ph", "pho", "ho", "hon", "on", "one", "ne"]);
+        assert_eq!(query, vec!["phon"]);
+        assert_eq!(analyze("on", AnalysisMode::Query), vec!["on"]);
+    }
+}
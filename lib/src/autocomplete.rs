@@ -0,0 +1,125 @@
+use std::collections::HashMap;
+
+use crate::analyzer::{AnalysisMode, Analyzer};
+use crate::collection::Collection;
+use crate::field::{Field, FieldValue};
+use crate::query::QueryError;
+use crate::term::Term;
+
+impl Collection {
+    /// Completes the last word of the query with words of the string fields, the most common first.
+    /// Returns whole queries with the last word completed, nothing if the query ends with a space.
+    pub fn autocomplete(
+        &self,
+        query: &str,
+        fields: Option<Vec<String>>,
+        max: Option<usize>,
+    ) -> Result<Vec<String>, QueryError> {
+        let max = max.unwrap_or(10);
+
+        if max == 0 {
+            return Ok(Vec::new());
+        }
+
+        let fields = match fields {
+            Some(fields) => self.get_fields(&fields)?,
+            None => self.fields.iter().collect(),
+        };
+
+        let last_word = match query.split_whitespace().last() {
+            Some(word) if !query.ends_with(char::is_whitespace) => word,
+            _ => return Ok(Vec::new()),
+        };
+        let head = &query[..query.len() - last_word.len()];
+
+        //number of documents with the word, summed over the fields
+        let mut words: HashMap<&String, (usize, &Field, &Term)> = HashMap::new();
+        //built once per field, candidates are normalized with them too
+        let mut analyzers: HashMap<&str, Box<dyn Analyzer>> = HashMap::new();
+
+        for field in fields {
+            if !matches!(field.value, FieldValue::String(_, _)) {
+                continue;
+            }
+
+            let analyzer = self.word_analyzer(field, AnalysisMode::Index);
+
+            for (word, entry) in field.inverted_index.words(&Self::normalize_with(analyzer.as_ref(), last_word)) {
+                words.entry(word).or_insert((0, field, &entry.term)).0 += entry.docs;
+            }
+
+            analyzers.insert(&field.name, analyzer);
+        }
+
+        let mut words: Vec<(&String, (usize, &Field, &Term))> = words.into_iter().collect();
+        words.sort_by(|x, y| y.1 .0.cmp(&x.1 .0).then_with(|| x.0.cmp(y.0)));
+
+        let mut completions: Vec<String> = Vec::new();
+
+        for (word, (_, field, term)) in words {
+            //the original spelling, when the first occurrence of the term is the same word
+            let word = self
+                .original_word(field, term)
+                .filter(|x| Self::normalize_with(analyzers[field.name.as_str()].as_ref(), x) == *word)
+                .unwrap_or_else(|| word.clone());
+            let completion = format!("{}{}", head, word);
+
+            if !completions.contains(&completion) {
+                completions.push(completion);
+            }
+
+            if completions.len() == max {
+                break;
+            }
+        }
+
+        Ok(completions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::Document;
+
+    #[test]
+    fn test_autocomplete() {
+        let mut collection = Collection::new();
+        collection.push_field("title", FieldValue::String(String::new(), None));
+
+        for title in ["Dream big", "Sweet dream", "Drama club"] {
+            let mut doc = Document::new();
+            doc.push("title", FieldValue::String(title.into(), None));
+            collection.push(doc, None);
+        }
+
+        collection.commit();
+
+        assert_eq!(collection.autocomplete("sweet dr", None, None).unwrap(), vec!["sweet dream", "sweet drama"]);
+        assert_eq!(collection.autocomplete("sweet dr", None, Some(1)).unwrap(), vec!["sweet dream"]);
+        assert!(collection.autocomplete("sweet dr", None, Some(0)).unwrap().is_empty());
+        assert_eq!(collection.autocomplete("Dra", None, None).unwrap(), vec!["drama"]);
+        assert!(collection.autocomplete("sweet ", None, None).unwrap().is_empty());
+        assert!(collection.autocomplete("sweet x", None, None).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_autocomplete_unstemmed() {
+        let mut collection = Collection::new();
+        collection.push_field("title", FieldValue::String(String::new(), None));
+
+        for title in ["Dreaming of Rust", "Dreams come true", "Café au lait"] {
+            let mut doc = Document::new();
+            doc.push("title", FieldValue::String(title.into(), None));
+            collection.push(doc, None);
+        }
+
+        collection.commit();
+
+        let complete = |query| collection.autocomplete(query, None, None).unwrap();
+
+        assert_eq!(complete("learn dreami"), vec!["learn dreaming"]);
+        assert_eq!(complete("Dream"), vec!["dreaming", "dreams"]);
+        assert_eq!(complete("cafe"), vec!["café"]);
+    }
+}
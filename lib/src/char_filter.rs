@@ -0,0 +1,179 @@
+/// Text after a character filter, with the byte offsets in the original text every byte of it came from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FilteredText {
+    pub text: String,
+    /// Start and end of the original text of every byte.
+    pub offsets: Vec<(usize, usize)>,
+}
+
+impl FilteredText {
+    pub fn new(text: &str) -> Self {
+        let mut offsets = Vec::with_capacity(text.len());
+
+        for (start, letter) in text.char_indices() {
+            let end = start + letter.len_utf8();
+            offsets.extend(std::iter::repeat_n((start, end), letter.len_utf8()));
+        }
+
+        FilteredText {
+            text: text.into(),
+            offsets,
+        }
+    }
+
+    fn push(&mut self, letter: char, start: usize, end: usize) {
+        self.text.push(letter);
+        self.offsets.extend(std::iter::repeat_n((start, end), letter.len_utf8()));
+    }
+
+    /// Applies the filter, the offsets still point into the original text.
+    pub fn then(self, filter: &dyn CharFilter) -> FilteredText {
+        let mut result = filter.filter(&self.text);
+
+        for offset in result.offsets.iter_mut() {
+            *offset = (self.offsets[offset.0].0, self.offsets[offset.1 - 1].1);
+        }
+
+        result
+    }
+
+    /// Span of the original text the bytes `start..end` came from.
+    pub fn original(&self, start: usize, end: usize) -> (usize, usize) {
+        match end > start {
+            true => (self.offsets[start].0, self.offsets[end - 1].1),
+            false => (self.offsets[start].0, self.offsets[start].0),
+        }
+    }
+}
+
+/// Stage of an analyzer before the tokenizer, changes the text itself.
+pub trait CharFilter: Send + Sync {
+    fn filter(&self, text: &str) -> FilteredText;
+}
+
+/// Strips HTML tags, comments, scripts and styles and decodes entities (`&amp;` -> `&`).
+/// Tags of elements which don't separate words (`<b>`, `<em>`...) are removed, the others become a space.
+pub struct HtmlStripFilter;
+
+const INLINE_TAGS: [&str; 17] = [
+    "a", "abbr", "b", "bdi", "bdo", "cite", "code", "em", "font", "i", "mark", "s", "small", "span", "strong", "sub", "sup",
+];
+
+impl HtmlStripFilter {
+    /// End of the tag starting at `start`, `None` if it isn't a tag.
+    pub(crate) fn tag_end(text: &str, start: usize) -> Option<usize> {
+        let rest = &text[start..];
+
+        if rest.starts_with("<!--") {
+            return Some(rest.find("-->").map(|x| start + x + 3).unwrap_or(text.len()));
+        }
+
+        match rest[1..].chars().next() {
+            Some(x) if x.is_ascii_alphabetic() || x == '/' || x == '!' || x == '?' => {}
+            _ => return None,
+        }
+
+        rest.find('>').map(|x| start + x + 1)
+    }
+
+    fn tag_name(tag: &str) -> String {
+        tag.trim_start_matches(['<', '/'])
+            .chars()
+            .take_while(|x| x.is_ascii_alphanumeric())
+            .collect::<String>()
+            .to_ascii_lowercase()
+    }
+
+    /// Decoded entity starting at `start` and its end, `None` if it isn't an entity.
+    fn entity(text: &str, start: usize) -> Option<(char, usize)> {
+        let rest = &text[start + 1..];
+        let length = rest.find(';').filter(|x| *x <= 10)?;
+        let name = &rest[..length];
+
+        let letter = match name {
+            "amp" => '&',
+            "lt" => '<',
+            "gt" => '>',
+            "quot" => '"',
+            "apos" => '\'',
+            "nbsp" => ' ',
+            _ => {
+                let code = match name.strip_prefix('#')? {
+                    x if x.starts_with(['x', 'X']) => u32::from_str_radix(&x[1..], 16).ok()?,
+                    x => x.parse().ok()?,
+                };
+                char::from_u32(code)?
+            }
+        };
+
+        Some((letter, start + length + 2))
+    }
+}
+
+impl CharFilter for HtmlStripFilter {
+    fn filter(&self, text: &str) -> FilteredText {
+        let mut result = FilteredText {
+            text: String::new(),
+            offsets: Vec::new(),
+        };
+        let mut i = 0;
+
+        while let Some(letter) = text[i..].chars().next() {
+            if letter == '<' {
+                if let Some(end) = Self::tag_end(text, i) {
+                    let name = Self::tag_name(&text[i..end]);
+
+                    //skip the contents of scripts and styles along with the tags
+                    let end = match name.as_str() {
+                        "script" | "style" if !text[i..].starts_with("</") => {
+                            let close = format!("</{}", name);
+                            let lowercase = text[end..].to_ascii_lowercase();
+                            lowercase.find(&close).and_then(|x| Self::tag_end(text, end + x)).unwrap_or(text.len())
+                        }
+                        _ => end,
+                    };
+
+                    if !INLINE_TAGS.contains(&name.as_str()) {
+                        result.push(' ', i, end);
+                    }
+
+                    i = end;
+                    continue;
+                }
+            }
+
+            if letter == '&' {
+                if let Some((decoded, end)) = Self::entity(text, i) {
+                    result.push(decoded, i, end);
+                    i = end;
+                    continue;
+                }
+            }
+
+            result.push(letter, i, i + letter.len_utf8());
+            i += letter.len_utf8();
+        }
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_html_strip() {
+        let html = "<p>Fish &amp; <b>Ch</b>ips</p><script>var a = 1;</script>caf&#233;";
+        let result = HtmlStripFilter.filter(html);
+
+        assert_eq!(result.text, " Fish & Chips  café");
+
+        let start = result.text.find('&').unwrap();
+        assert_eq!(result.original(start, start + 1), (8, 13));
+
+        let start = result.text.find("Chips").unwrap();
+        let (start, end) = result.original(start, start + 5);
+        assert_eq!(&html[start..end], "Ch</b>ips");
+    }
+}
@@ -4,15 +4,24 @@ use std::io::Error;
 
 use bincode::{Decode, Encode};
 
+use crate::analyzer::{standard_analyzer, AnalysisMode, Analyzer, AnalyzerConfig, KeywordAnalyzer};
 use crate::document::Document;
 use crate::field::{Field, FieldValue};
 use crate::file::File;
 use crate::inverted_index::InvertedIndex;
+use crate::numeric_index::NumericIndex;
+use crate::synonym::Synonym;
+use crate::term::Term;
+use crate::token::Token;
 
 #[derive(Default, Encode, Decode)]
 pub struct Collection {
     pub documents: HashMap<i32, Document>,
     pub fields: Vec<Field>,
+    /// Query terms are expanded with them at search time.
+    pub synonyms: Vec<Synonym>,
+    /// Stop words of every string field in addition to their own.
+    pub stop_words: Vec<String>,
     pub file_name: Option<String>,
     pub(crate) last_index: i32,
 }
@@ -23,30 +32,72 @@ impl Collection {
     }
 
     pub fn push_field(&mut self, name: &str, value: FieldValue) {
+        self.push_field_with_analyzer(name, value, AnalyzerConfig::default());
+    }
+
+    /// Adds a field whose string values are analyzed with the configured analyzer.
+    pub fn push_field_with_analyzer(&mut self, name: &str, value: FieldValue, analyzer: AnalyzerConfig) {
         let name = name.to_string();
         let inverted_index = InvertedIndex::new();
+        let numeric_index = NumericIndex::new();
 
         self.fields.push(Field {
             name,
             value,
             inverted_index,
+            numeric_index,
+            analyzer,
         });
     }
 
     pub fn commit(&mut self) {
+        //words only differ from the terms when they get stemmed
+        let analyzers: Vec<_> = self
+            .fields
+            .iter()
+            .map(|x| {
+                let words = x.analyzer.language.map(|_| self.word_analyzer(x, AnalysisMode::Index));
+                (self.analyzer(x, AnalysisMode::Index), words)
+            })
+            .collect();
+
         //iterate over fields
-        for field in self.fields.iter_mut() {
+        for (field, (analyzer, word_analyzer)) in self.fields.iter_mut().zip(analyzers) {
+            //rebuild the index from scratch, so positions don't get duplicated
+            field.inverted_index.clear();
+
             //iterate over documents
             for doc in self.documents.iter_mut() {
-                let value = doc.1.process_field(field.name.as_str());
+                let value = doc.1.process_field(field.name.as_str(), analyzer.as_ref());
 
-                if let Some(FieldValue::String(_, tokens)) = value {
-                    if let Some(tokens) = tokens {
-                        for token in tokens {
-                            field.inverted_index.push(token.clone(), *doc.0);
+                if let Some(FieldValue::String(text, Some(tokens))) = value {
+                    for token in tokens.iter() {
+                        field.inverted_index.push(token.term.clone(), *doc.0, token.position);
+                    }
+
+                    let words = word_analyzer.as_ref().map(|x| x.analyze(text));
+                    let words = words.as_deref().unwrap_or(tokens);
+
+                    //stemming keeps every token, so the words line up with the terms
+                    if words.len() == tokens.len() {
+                        for (word, token) in words.iter().zip(tokens.iter()) {
+                            field.inverted_index.push_word(&word.term.value, &token.term, *doc.0);
                         }
                     }
                 }
+
+                if let Some(FieldValue::Keyword(value)) = doc.1.get(field.name.as_str()) {
+                    field.inverted_index.push(value.as_str().into(), *doc.0, 0);
+                }
+            }
+
+            if let FieldValue::Int(_) = field.value {
+                field.numeric_index = NumericIndex::build(self.documents.iter().filter_map(|(id, doc)| {
+                    match doc.get(field.name.as_str()) {
+                        Some(FieldValue::Int(value)) => Some((*value, *id)),
+                        _ => None,
+                    }
+                }));
             }
         }
     }
@@ -80,6 +131,66 @@ impl Collection {
         self.fields.iter().find(|x| x.name == *name)
     }
 
+    /// Analyzer of the field for the mode, the standard one if the field's analyzer isn't registered.
+    /// Only string fields are analyzed, the others keep the whole text as it is (`-5` stays a negative number).
+    pub fn analyzer(&self, field: &Field, mode: AnalysisMode) -> Box<dyn Analyzer> {
+        self.build_analyzer(field, &field.analyzer, mode)
+    }
+
+    /// Analyzer of the field which doesn't stem the words, to look them up by prefix.
+    pub fn word_analyzer(&self, field: &Field, mode: AnalysisMode) -> Box<dyn Analyzer> {
+        let config = AnalyzerConfig {
+            language: None,
+            ..field.analyzer.clone()
+        };
+
+        self.build_analyzer(field, &config, mode)
+    }
+
+    fn build_analyzer(&self, field: &Field, config: &AnalyzerConfig, mode: AnalysisMode) -> Box<dyn Analyzer> {
+        if !matches!(field.value, FieldValue::String(_, _)) {
+            return Box::new(KeywordAnalyzer);
+        }
+
+        let config = config.with_mode(mode, &self.stop_words);
+
+        config
+            .build(mode)
+            .unwrap_or_else(|| Box::new(standard_analyzer(&config)))
+    }
+
+    pub fn analyze(&self, field: &Field, text: &str, mode: AnalysisMode) -> Vec<Token> {
+        self.analyzer(field, mode).analyze(text)
+    }
+
+    /// Word as the field keeps it before stemming: normalized, lowercased and folded (`Café` -> `cafe`).
+    pub(crate) fn normalize_word(&self, field: &Field, word: &str) -> String {
+        Self::normalize_with(self.word_analyzer(field, AnalysisMode::Index).as_ref(), word)
+    }
+
+    /// Same as `normalize_word` with the word analyzer of the field already built.
+    pub(crate) fn normalize_with(analyzer: &dyn Analyzer, word: &str) -> String {
+        analyzer
+            .analyze(word)
+            .into_iter()
+            .next()
+            .map(|x| x.term.value)
+            .unwrap_or_else(|| word.to_lowercase())
+    }
+
+    /// Word of the original text the term was produced from, lowercased.
+    pub(crate) fn original_word(&self, field: &Field, term: &Term) -> Option<String> {
+        let (id, positions) = field.inverted_index.get(term)?.iter().next()?;
+
+        match self.get(*id)?.get(field.name.as_str())? {
+            FieldValue::String(text, Some(tokens)) => {
+                let token = tokens.iter().find(|x| x.position == positions[0])?;
+                Some(text[token.start..token.end].to_lowercase())
+            }
+            _ => None,
+        }
+    }
+
     pub fn is_empty(&self) -> bool {
         self.documents.is_empty()
     }
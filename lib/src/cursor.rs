@@ -0,0 +1,71 @@
+use bincode::{config, Decode, Encode};
+
+use crate::sort::SortValue;
+
+/// Most bytes a decoded cursor may take.
+const MAX_SIZE: usize = 1 << 16;
+
+/// Position of a hit in the sorted results, used to continue from it on the next page.
+/// It is passed around as an opaque hex string.
+#[derive(Debug, Clone, PartialEq, Encode, Decode)]
+pub struct Cursor {
+    pub values: Vec<SortValue>,
+    pub id: i32,
+}
+
+impl Cursor {
+    pub fn encode(&self) -> String {
+        let bytes = bincode::encode_to_vec(self, config::standard()).expect("Valid cursor");
+
+        bytes.iter().map(|x| format!("{:02x}", x)).collect()
+    }
+
+    pub fn decode(cursor: &str) -> Option<Cursor> {
+        if !cursor.len().is_multiple_of(2) || !cursor.is_ascii() {
+            return None;
+        }
+
+        let bytes = (0..cursor.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&cursor[i..i + 2], 16).ok())
+            .collect::<Option<Vec<u8>>>()?;
+
+        //the limit keeps a forged length from allocating huge vectors
+        bincode::decode_from_slice(&bytes, config::standard().with_limit::<MAX_SIZE>())
+            .ok()
+            .map(|x| x.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::field::FieldValue;
+
+    #[test]
+    fn test_encode() {
+        let cursor = Cursor {
+            values: vec![SortValue::Field(Some(FieldValue::Int(1700000000))), SortValue::Score(0.5)],
+            id: 42,
+        };
+
+        assert_eq!(Cursor::decode(&cursor.encode()), Some(cursor));
+        assert_eq!(Cursor::decode("zz"), None);
+    }
+
+    #[test]
+    fn test_decode_malformed() {
+        let encoded = Cursor {
+            values: vec![SortValue::Score(0.5)],
+            id: 42,
+        }
+        .encode();
+
+        assert_eq!(Cursor::decode(""), None);
+        assert_eq!(Cursor::decode("abc"), None);
+        assert_eq!(Cursor::decode("é1"), None);
+        assert_eq!(Cursor::decode(&encoded[..encoded.len() - 2]), None);
+        //length prefix of a vector with 2^63 values
+        assert_eq!(Cursor::decode("fdffffffffffffff7f"), None);
+    }
+}
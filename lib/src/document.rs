@@ -2,8 +2,8 @@ use std::collections::HashMap;
 
 use bincode::{Decode, Encode};
 
+use crate::analyzer::Analyzer;
 use crate::field::FieldValue;
-use crate::tokenizer::tokenize;
 
 #[derive(Debug, Default, Clone, Encode, Decode)]
 pub struct Document {
@@ -36,11 +36,11 @@ impl Document {
         self.fields.get_mut(field_name)
     }
 
-    pub fn process_field(&mut self, name: &str) -> Option<&mut FieldValue> {
+    pub fn process_field(&mut self, name: &str, analyzer: &dyn Analyzer) -> Option<&mut FieldValue> {
         match self.get_mut(name) {
             Some(field_value) => {
                 if let FieldValue::String(value, tokens) = field_value {
-                    *tokens = Some(tokenize(value.clone().as_str()));
+                    *tokens = Some(analyzer.analyze(value.as_str()));
                     return Some(field_value);
                 }
                 None
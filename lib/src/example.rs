@@ -4,6 +4,7 @@ use sightnet_core::collection::Collection;
 use sightnet_core::document::Document;
 use sightnet_core::field::FieldValue;
 use sightnet_core::file::File;
+use sightnet_core::search::SearchOptions;
 
 pub fn main() {
     let mut collection = Collection::new();
@@ -22,6 +23,6 @@ pub fn main() {
 
     collection.commit();
 
-    println!("{:#?}", collection.search("dream", false, None, None));
+    println!("{:#?}", collection.search("dream", &SearchOptions::default()).unwrap());
     File::save(&collection, "out.bin").unwrap();
 }
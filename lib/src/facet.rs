@@ -0,0 +1,109 @@
+use std::collections::HashMap;
+
+use crate::collection::Collection;
+use crate::field::Field;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Facet {
+    pub value: String,
+    pub count: usize,
+}
+
+impl Facet {
+    /// Counts how many of the documents have each value of the field.
+    /// Returns at most `size` values, the most common first.
+    pub fn count<'a>(
+        collection: &Collection,
+        field: &Field,
+        ids: impl Iterator<Item = &'a i32>,
+        size: usize,
+    ) -> Vec<Facet> {
+        let mut counts: HashMap<String, usize> = HashMap::new();
+
+        for id in ids {
+            let value = collection
+                .get(*id)
+                .and_then(|doc| doc.get(field.name.as_str()));
+
+            if let Some(value) = value {
+                *counts.entry(value.as_string()).or_default() += 1;
+            }
+        }
+
+        let mut facets: Vec<Facet> = counts
+            .into_iter()
+            .map(|(value, count)| Facet { value, count })
+            .collect();
+
+        facets.sort_by(|x, y| y.count.cmp(&x.count).then_with(|| x.value.cmp(&y.value)));
+        facets.truncate(size);
+        facets
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::Document;
+    use crate::field::FieldValue;
+    use crate::search::SearchOptions;
+
+    fn collection() -> Collection {
+        let mut collection = Collection::new();
+        collection.push_field("title", FieldValue::String(String::new(), None));
+        collection.push_field("category", FieldValue::String(String::new(), None));
+        collection.push_field("in_stock", FieldValue::Bool(false));
+
+        let docs = [
+            ("dream", "books", true),
+            ("dream", "music", false),
+            ("dream", "books", false),
+            ("dream", "films", true),
+            ("night", "music", true),
+        ];
+
+        for (title, category, in_stock) in docs {
+            let mut doc = Document::new();
+            doc.push("title", FieldValue::String(title.into(), None));
+            doc.push("category", FieldValue::String(category.into(), None));
+            doc.push("in_stock", FieldValue::Bool(in_stock));
+            collection.push(doc, None);
+        }
+
+        collection.commit();
+        collection
+    }
+
+    fn facets(values: &[(&str, usize)]) -> Vec<Facet> {
+        values.iter().map(|(value, count)| Facet { value: value.to_string(), count: *count }).collect()
+    }
+
+    #[test]
+    fn test_count() {
+        let collection = collection();
+        let field = &collection.fields[1];
+        let ids: Vec<i32> = collection.iter().map(|x| *x.0).collect();
+
+        assert_eq!(
+            Facet::count(&collection, field, ids.iter(), 10),
+            facets(&[("books", 2), ("music", 2), ("films", 1)])
+        );
+        assert_eq!(Facet::count(&collection, field, ids.iter(), 1), facets(&[("books", 2)]));
+        assert!(Facet::count(&collection, field, [].iter(), 10).is_empty());
+    }
+
+    #[test]
+    fn test_search_facets() {
+        let collection = collection();
+        let options = SearchOptions {
+            facets: vec!["category".into(), "in_stock".into()],
+            facet_size: Some(2),
+            ..Default::default()
+        };
+        let result = collection.search("dream", &options).unwrap();
+
+        //only the matching documents are counted
+        assert_eq!(result.facets["category"], facets(&[("books", 2), ("films", 1)]));
+        assert_eq!(result.facets["in_stock"], facets(&[("false", 2), ("true", 2)]));
+    }
+}
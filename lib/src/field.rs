@@ -2,22 +2,27 @@ use std::str::FromStr;
 
 use bincode::{Decode, Encode};
 
+use crate::analyzer::AnalyzerConfig;
 use crate::inverted_index::InvertedIndex;
-use crate::term::Term;
+use crate::numeric_index::NumericIndex;
+use crate::token::Token;
 
 #[derive(Debug, Clone, Encode, Decode)]
 pub enum FieldValue {
     Int(i64),
     Bool(bool),
-    String(String, Option<Vec<Term>>),
+    String(String, Option<Vec<Token>>),
+    /// String indexed as a whole exactly as it is, for ids, tags and enums.
+    Keyword(String),
 }
 
 impl FieldValue {
-    fn as_string(&self) -> String {
+    pub fn as_string(&self) -> String {
         match self {
             FieldValue::Int(val) => val.to_string(),
             FieldValue::Bool(val) => val.to_string(),
             FieldValue::String(val, _) => val.into(),
+            FieldValue::Keyword(val) => val.into(),
         }
     }
 }
@@ -28,6 +33,7 @@ impl ToString for FieldValue {
             FieldValue::Int(_) => "int".into(),
             FieldValue::Bool(_) => "bool".into(),
             FieldValue::String(_, _) => "string".into(),
+            FieldValue::Keyword(_) => "keyword".into(),
         }
     }
 }
@@ -40,6 +46,7 @@ impl FromStr for FieldValue {
             "int" => Ok(FieldValue::Int(0)),
             "bool" => Ok(FieldValue::Bool(false)),
             "string" => Ok(FieldValue::String("".into(), None)),
+            "keyword" => Ok(FieldValue::Keyword("".into())),
             _ => Err(()),
         }
     }
@@ -56,6 +63,8 @@ pub struct Field {
     pub name: String,
     pub value: FieldValue,
     pub inverted_index: InvertedIndex,
+    pub numeric_index: NumericIndex,
+    pub analyzer: AnalyzerConfig,
 }
 
 impl Field {}
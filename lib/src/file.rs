@@ -8,12 +8,19 @@ use fs2::FileExt;
 
 use crate::collection::Collection;
 
+/// Starts every db file, files without it were written before the format had a version.
+const MAGIC: &[u8; 4] = b"SNDB";
+const VERSION: u32 = 1;
+
 pub struct File {}
 
 impl File {
     pub fn save(collection: &Collection, file_name: &str) -> Result<(), io::Error> {
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend_from_slice(&VERSION.to_le_bytes());
+        bincode::encode_into_std_write(collection, &mut bytes, config::standard()).map_err(invalid_data)?;
+
         let mut file = fs::File::create(file_name)?;
-        let bytes = bincode::encode_to_vec(collection, config::standard()).expect("Valide collection");
 
         file.lock_exclusive()?;
         file.write_all(&bytes)?;
@@ -30,7 +37,165 @@ impl File {
         file.read_to_end(&mut bytes)?;
         file.unlock()?;
 
-        let collection: Collection = bincode::decode_from_slice(&bytes, config::standard()).expect("Valide db file").0;
+        match bytes.strip_prefix(MAGIC) {
+            Some(rest) if rest.len() >= 4 => {
+                let version = u32::from_le_bytes(rest[..4].try_into().unwrap());
+                if version != VERSION {
+                    return Err(invalid_data(format!("Unsupported db file version {}", version)));
+                }
+
+                let collection: Collection = bincode::decode_from_slice(&rest[4..], config::standard()).map_err(invalid_data)?.0;
+                Ok(collection)
+            }
+            Some(_) => Err(invalid_data("Truncated db file header")),
+            None => legacy::load(&bytes),
+        }
+    }
+}
+
+fn invalid_data<E: Into<Box<dyn std::error::Error + Send + Sync>>>(error: E) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, error)
+}
+
+/// Layout of the files written before the version header, their documents get indexed again.
+mod legacy {
+    use std::collections::HashMap;
+    use std::io;
+
+    use bincode::{config, Decode, Encode};
+
+    use crate::collection::Collection;
+    use crate::document::Document;
+    use crate::field::FieldValue as CurrentValue;
+    use crate::term::Term;
+
+    #[derive(Encode, Decode)]
+    pub(super) struct Collection0 {
+        pub documents: HashMap<i32, Document0>,
+        pub fields: Vec<Field0>,
+        pub file_name: Option<String>,
+        pub last_index: i32,
+    }
+
+    #[derive(Encode, Decode)]
+    pub(super) struct Field0 {
+        pub name: String,
+        pub value: FieldValue0,
+        pub inverted_index: HashMap<Term, Vec<i32>>,
+    }
+
+    #[derive(Encode, Decode)]
+    pub(super) enum FieldValue0 {
+        Int(i64),
+        Bool(bool),
+        String(String, Option<Vec<Term>>),
+    }
+
+    #[derive(Encode, Decode)]
+    pub(super) struct Document0 {
+        pub fields: HashMap<String, FieldValue0>,
+    }
+
+    impl From<FieldValue0> for CurrentValue {
+        fn from(value: FieldValue0) -> Self {
+            match value {
+                FieldValue0::Int(val) => CurrentValue::Int(val),
+                FieldValue0::Bool(val) => CurrentValue::Bool(val),
+                FieldValue0::String(val, _) => CurrentValue::String(val, None),
+            }
+        }
+    }
+
+    pub(super) fn load(bytes: &[u8]) -> Result<Collection, io::Error> {
+        let old: Collection0 = bincode::decode_from_slice(bytes, config::standard()).map_err(super::invalid_data)?.0;
+        let mut collection = Collection::new();
+
+        for field in old.fields {
+            collection.push_field(&field.name, field.value.into());
+        }
+
+        for (index, old_document) in old.documents {
+            let mut document = Document::new();
+            for (name, value) in old_document.fields {
+                document.push(&name, value.into());
+            }
+            collection.push(document, Some(index));
+        }
+
+        collection.file_name = old.file_name;
+        collection.last_index = old.last_index;
+        collection.commit();
+
         Ok(collection)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::legacy::{Collection0, Document0, Field0, FieldValue0};
+    use super::*;
+    use crate::document::Document;
+    use crate::field::FieldValue;
+    use crate::search::SearchOptions;
+
+    fn temp_file(name: &str) -> String {
+        std::env::temp_dir().join(format!("sightnet_{}_{}.bin", name, std::process::id())).to_string_lossy().into_owned()
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let file_name = temp_file("round_trip");
+        let mut collection = Collection::new();
+        collection.push_field("title", FieldValue::String(String::new(), None));
+        let mut doc = Document::new();
+        doc.push("title", FieldValue::String("Sweet dreams".to_string(), None));
+        collection.push(doc, None);
+        collection.commit();
+
+        File::save(&collection, &file_name).unwrap();
+        let loaded = File::load(&file_name).unwrap();
+        fs::remove_file(&file_name).unwrap();
+
+        let hits = loaded.search("dreams", &SearchOptions::default()).unwrap();
+        assert_eq!(hits.hits.iter().map(|x| x.id).collect::<Vec<_>>(), vec![0]);
+    }
+
+    #[test]
+    fn test_invalid_file() {
+        let file_name = temp_file("invalid");
+
+        fs::write(&file_name, b"not a collection").unwrap();
+        let legacy = File::load(&file_name);
+        fs::write(&file_name, b"SNDB\x02\0\0\0").unwrap();
+        let version = File::load(&file_name);
+        fs::remove_file(&file_name).unwrap();
+
+        assert_eq!(legacy.err().map(|x| x.kind()), Some(io::ErrorKind::InvalidData));
+        assert_eq!(version.err().map(|x| x.kind()), Some(io::ErrorKind::InvalidData));
+    }
+
+    #[test]
+    fn test_legacy_file() {
+        let file_name = temp_file("legacy");
+        let text = FieldValue0::String("Sweet dreams".to_string(), None);
+        let old = Collection0 {
+            documents: HashMap::from([(3, Document0 { fields: HashMap::from([("title".to_string(), text)]) })]),
+            fields: vec![Field0 { name: "title".to_string(), value: FieldValue0::String(String::new(), None), inverted_index: HashMap::new() }],
+            file_name: Some(file_name.clone()),
+            last_index: 4,
+        };
+
+        fs::write(&file_name, bincode::encode_to_vec(old, config::standard()).unwrap()).unwrap();
+        let mut loaded = File::load(&file_name).unwrap();
+        fs::remove_file(&file_name).unwrap();
+
+        let hits = loaded.search("dream", &SearchOptions::default()).unwrap();
+        assert_eq!(hits.hits.iter().map(|x| x.id).collect::<Vec<_>>(), vec![3]);
+        assert_eq!(loaded.file_name, Some(file_name));
+
+        loaded.push(Document::new(), None);
+        assert!(loaded.documents.contains_key(&4));
+    }
+}
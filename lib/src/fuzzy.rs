@@ -0,0 +1,82 @@
+use crate::inverted_index::InvertedIndex;
+use crate::term::Term;
+
+/// Number of typos allowed in a term, longer terms are allowed more.
+pub fn max_typos(term: &str) -> usize {
+    match term.chars().count() {
+        0..=3 => 0,
+        4..=7 => 1,
+        _ => 2,
+    }
+}
+
+/// Damerau-Levenshtein (optimal string alignment) distance between the strings,
+/// `None` if it is greater than `max`. Swapped neighbouring characters count as a single edit.
+pub fn distance(a: &str, b: &str, max: usize) -> Option<usize> {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+
+    if a.len().abs_diff(b.len()) > max {
+        return None;
+    }
+
+    let mut before_previous = vec![0; b.len() + 1];
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+    let mut previous_min = 0;
+
+    for i in 1..=a.len() {
+        current[0] = i;
+        let mut current_min = i;
+
+        for j in 1..=b.len() {
+            let cost = (a[i - 1] != b[j - 1]) as usize;
+            current[j] = (previous[j] + 1).min(current[j - 1] + 1).min(previous[j - 1] + cost);
+
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                current[j] = current[j].min(before_previous[j - 2] + 1);
+            }
+
+            current_min = current_min.min(current[j]);
+        }
+
+        //next rows can't get below these minimums
+        if current_min > max && previous_min >= max {
+            return None;
+        }
+
+        previous_min = current_min;
+        std::mem::swap(&mut before_previous, &mut previous);
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    Some(previous[b.len()]).filter(|x| *x <= max)
+}
+
+/// Terms of the index within the allowed number of typos from the term, with their distances.
+pub fn expand<'a>(inverted_index: &'a InvertedIndex, term: &Term) -> Vec<(&'a Term, usize)> {
+    let max = max_typos(&term.value);
+
+    if max == 0 {
+        return inverted_index.get_key_value(term).map(|(x, _)| (x, 0)).into_iter().collect();
+    }
+
+    inverted_index
+        .terms()
+        .filter_map(|(x, _)| Some((x, distance(&term.value, &x.value, max)?)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_distance() {
+        assert_eq!(distance("search", "search", 2), Some(0));
+        assert_eq!(distance("serach", "search", 2), Some(1));
+        assert_eq!(distance("kitten", "sitting", 3), Some(3));
+        assert_eq!(distance("kitten", "sitting", 2), None);
+        assert_eq!(distance("rust", "trust", 1), Some(1));
+    }
+}
@@ -0,0 +1,179 @@
+use std::collections::HashSet;
+
+use crate::char_filter::HtmlStripFilter;
+use crate::term::Term;
+use crate::token::Token;
+
+#[derive(Debug, Clone)]
+pub struct HighlightOptions {
+    /// Fields to highlight, all string fields by default.
+    pub fields: Option<Vec<String>>,
+    pub pre_tag: String,
+    pub post_tag: String,
+    /// Number of words in the snippet.
+    pub snippet_size: usize,
+}
+
+impl Default for HighlightOptions {
+    fn default() -> Self {
+        HighlightOptions {
+            fields: None,
+            pre_tag: "<em>".into(),
+            post_tag: "</em>".into(),
+            snippet_size: 20,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Highlight {
+    /// Whole field value with the matched terms wrapped in tags.
+    pub fragment: String,
+    /// Part of the field value with the most matched terms.
+    pub snippet: String,
+}
+
+impl Highlight {
+    /// Highlights the tokens of the text which are among the terms, `None` if there are no such tokens.
+    pub fn new(text: &str, tokens: &[Token], terms: &HashSet<Term>, options: &HighlightOptions) -> Option<Highlight> {
+        let matches: Vec<bool> = tokens.iter().map(|x| terms.contains(&x.term)).collect();
+
+        if !matches.contains(&true) {
+            return None;
+        }
+
+        let fragment = Self::mark(text, tokens, &matches, 0, text.len(), options);
+
+        //slide a window of `snippet_size` tokens and keep the one with the most matches
+        let size = options.snippet_size.clamp(1, tokens.len());
+        let mut count = matches[..size].iter().filter(|x| **x).count();
+        let mut best = (count, 0);
+
+        for i in 1..=tokens.len() - size {
+            count = count + matches[i + size - 1] as usize - matches[i - 1] as usize;
+
+            if count > best.0 {
+                best = (count, i);
+            }
+        }
+
+        let first = best.1;
+        let last = first + size - 1;
+        let start = if first == 0 { 0 } else { tokens[first].start };
+        let end = if last == tokens.len() - 1 { text.len() } else { tokens[last].end };
+
+        let mut snippet = Self::mark(text, &tokens[first..=last], &matches[first..=last], start, end, options);
+
+        if first > 0 {
+            snippet.insert_str(0, "...");
+        }
+
+        if last < tokens.len() - 1 {
+            snippet.push_str("...");
+        }
+
+        Some(Highlight { fragment, snippet })
+    }
+
+    fn mark(text: &str, tokens: &[Token], matches: &[bool], start: usize, end: usize, options: &HighlightOptions) -> String {
+        let mut result = String::new();
+        let mut last = start;
+        let mut spans: Vec<(usize, usize)> = Vec::new();
+
+        //tokens can overlap (CJK bigrams) or share offsets, overlapping ones are wrapped as one span
+        for token in tokens.iter().zip(matches).filter(|x| *x.1).map(|x| x.0) {
+            match spans.last_mut() {
+                Some(span) if token.start < span.1 => span.1 = span.1.max(token.end),
+                _ => spans.push((token.start, token.end)),
+            }
+        }
+
+        for (span_start, span_end) in spans {
+            result.push_str(&text[last..span_start]);
+            Self::wrap(&mut result, &text[span_start..span_end], options);
+            last = span_end;
+        }
+
+        result.push_str(&text[last..end]);
+        result
+    }
+
+    /// Wraps the text runs of the span separately, so tags the span spans over (`Dr</b>eams`) stay balanced.
+    fn wrap(result: &mut String, span: &str, options: &HighlightOptions) {
+        let mut run = 0;
+        let mut i = 0;
+
+        while let Some(start) = span[i..].find('<').map(|x| i + x) {
+            let Some(end) = HtmlStripFilter::tag_end(span, start) else {
+                i = start + 1;
+                continue;
+            };
+
+            if start > run {
+                result.push_str(&options.pre_tag);
+                result.push_str(&span[run..start]);
+                result.push_str(&options.post_tag);
+            }
+
+            result.push_str(&span[start..end]);
+            run = end;
+            i = end;
+        }
+
+        if span.len() > run {
+            result.push_str(&options.pre_tag);
+            result.push_str(&span[run..]);
+            result.push_str(&options.post_tag);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::{standard_analyzer, Analyzer, AnalyzerConfig};
+    use crate::tokenizer::{tokenize_with_positions, CjkTokenizer, Tokenizer};
+
+    #[test]
+    fn test_highlight() {
+        let text = "Rust is fast. Rust is safe, and it is fun to write.";
+        let tokens = tokenize_with_positions(text);
+        let terms = HashSet::from(["safe".into(), "fun".into()]);
+        let options = HighlightOptions {
+            snippet_size: 5,
+            ..Default::default()
+        };
+
+        let highlight = Highlight::new(text, &tokens, &terms, &options).unwrap();
+
+        assert_eq!(
+            highlight.fragment,
+            "Rust is fast. Rust is <em>safe</em>, and it is <em>fun</em> to write."
+        );
+        assert_eq!(highlight.snippet, "...<em>safe</em>, and it is <em>fun</em>...");
+    }
+    #[test]
+    fn test_highlight_html() {
+        let text = "<p><b>Dr</b>eams &amp; <i>hopes</i></p>";
+        let config = AnalyzerConfig {
+            html: true,
+            ..Default::default()
+        };
+        let tokens = standard_analyzer(&config).analyze(text);
+        let terms = HashSet::from(["dream".into(), "hope".into()]);
+
+        let highlight = Highlight::new(text, &tokens, &terms, &HighlightOptions::default()).unwrap();
+
+        assert_eq!(highlight.fragment, "<p><b><em>Dr</em></b><em>eams</em> &amp; <i><em>hopes</em></i></p>");
+    }
+    #[test]
+    fn test_highlight_overlapping() {
+        let text = "東京都に住む";
+        let tokens = CjkTokenizer.tokenize(text);
+        let terms = HashSet::from(["東京".into(), "京都".into()]);
+
+        let highlight = Highlight::new(text, &tokens, &terms, &HighlightOptions::default()).unwrap();
+
+        assert_eq!(highlight.fragment, "<em>東京都</em>に住む");
+    }
+}
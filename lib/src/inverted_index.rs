@@ -1,27 +1,102 @@
-use std::collections::HashMap;
+use std::collections::BTreeMap;
+use std::ops::Bound;
 
 use bincode::{Decode, Encode};
 
 use crate::term::Term;
 
+/// Document id -> positions of the term inside the field, in ascending order.
+pub type Postings = BTreeMap<i32, Vec<usize>>;
+
+/// Word of the text as it is before stemming, with the term it is indexed as.
+#[derive(Debug, Clone, Encode, Decode)]
+pub struct Word {
+    pub term: Term,
+    /// Number of documents with the word.
+    pub docs: usize,
+    last_id: Option<i32>,
+}
+
+/// Terms are kept sorted, so the index doubles as a term dictionary for prefix lookups.
+/// Words are kept along with the terms, as stems don't share the prefixes of the words (`dreami*`).
 #[derive(Debug, Clone, Encode, Decode)]
 pub struct InvertedIndex {
-    index: HashMap<Term, Vec<i32>>,
+    index: BTreeMap<Term, Postings>,
+    words: BTreeMap<String, Word>,
+    tokens_count: usize,
 }
 
 impl InvertedIndex {
     pub fn new() -> InvertedIndex {
         InvertedIndex {
-            index: HashMap::new(),
+            index: BTreeMap::new(),
+            words: BTreeMap::new(),
+            tokens_count: 0,
         }
     }
 
-    pub fn push(&mut self, token: Term, id: i32) {
+    pub fn push(&mut self, token: Term, id: i32, position: usize) {
         let e = self.index.entry(token);
-        e.or_default().push(id);
+        e.or_default().entry(id).or_default().push(position);
+        self.tokens_count += 1;
+    }
+
+    /// Remembers the word of the document the term was produced from.
+    pub fn push_word(&mut self, word: &str, term: &Term, id: i32) {
+        let e = self.words.entry(word.to_string()).or_insert_with(|| Word {
+            term: term.clone(),
+            docs: 0,
+            last_id: None,
+        });
+
+        if e.last_id != Some(id) {
+            e.docs += 1;
+            e.last_id = Some(id);
+        }
     }
 
-    pub fn get(&self, term: &Term) -> Option<&Vec<i32>> {
+    pub fn get(&self, term: &Term) -> Option<&Postings> {
         self.index.get(term)
     }
+
+    /// Term stored in the index along with its postings.
+    pub fn get_key_value(&self, term: &Term) -> Option<(&Term, &Postings)> {
+        self.index.get_key_value(term)
+    }
+
+    pub fn positions(&self, term: &Term, id: i32) -> Option<&Vec<usize>> {
+        self.get(term)?.get(&id)
+    }
+
+    /// Terms starting with the prefix, in alphabetical order.
+    pub fn prefix<'a>(&'a self, prefix: &'a str) -> impl Iterator<Item = (&'a Term, &'a Postings)> + 'a {
+        self.index
+            .range((Bound::Included(Term::from(prefix)), Bound::Unbounded))
+            .take_while(move |(term, _)| term.value.starts_with(prefix))
+    }
+
+    /// Words starting with the prefix, in alphabetical order.
+    pub fn words<'a>(&'a self, prefix: &str) -> impl Iterator<Item = (&'a String, &'a Word)> + 'a {
+        let prefix = prefix.to_string();
+
+        self.words
+            .range::<str, _>((Bound::Included(prefix.as_str()), Bound::Unbounded))
+            .take_while(move |(word, _)| word.starts_with(&prefix))
+    }
+
+    /// All terms of the index, in alphabetical order.
+    pub fn terms(&self) -> impl Iterator<Item = (&Term, &Postings)> {
+        self.index.iter()
+    }
+
+    /// Number of tokens pushed into the index, including repeated ones.
+    pub fn tokens_count(&self) -> usize {
+        self.tokens_count
+    }
+
+    pub fn clear(&mut self) {
+        self.index.clear();
+        self.words.clear();
+        self.tokens_count = 0;
+    }
 }
@@ -0,0 +1,96 @@
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use bincode::{Decode, Encode};
+use rust_stemmers::Algorithm;
+
+/// Languages with a Snowball stemmer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Encode, Decode)]
+pub enum Language {
+    Arabic,
+    Danish,
+    Dutch,
+    English,
+    Finnish,
+    French,
+    German,
+    Greek,
+    Hungarian,
+    Italian,
+    Norwegian,
+    Portuguese,
+    Romanian,
+    Russian,
+    Spanish,
+    Swedish,
+    Tamil,
+    Turkish,
+}
+
+const LANGUAGES: [(Language, &str, &str); 18] = [
+    (Language::Arabic, "arabic", "ar"),
+    (Language::Danish, "danish", "da"),
+    (Language::Dutch, "dutch", "nl"),
+    (Language::English, "english", "en"),
+    (Language::Finnish, "finnish", "fi"),
+    (Language::French, "french", "fr"),
+    (Language::German, "german", "de"),
+    (Language::Greek, "greek", "el"),
+    (Language::Hungarian, "hungarian", "hu"),
+    (Language::Italian, "italian", "it"),
+    (Language::Norwegian, "norwegian", "no"),
+    (Language::Portuguese, "portuguese", "pt"),
+    (Language::Romanian, "romanian", "ro"),
+    (Language::Russian, "russian", "ru"),
+    (Language::Spanish, "spanish", "es"),
+    (Language::Swedish, "swedish", "sv"),
+    (Language::Tamil, "tamil", "ta"),
+    (Language::Turkish, "turkish", "tr"),
+];
+
+impl Language {
+    pub fn algorithm(&self) -> Algorithm {
+        match self {
+            Language::Arabic => Algorithm::Arabic,
+            Language::Danish => Algorithm::Danish,
+            Language::Dutch => Algorithm::Dutch,
+            Language::English => Algorithm::English,
+            Language::Finnish => Algorithm::Finnish,
+            Language::French => Algorithm::French,
+            Language::German => Algorithm::German,
+            Language::Greek => Algorithm::Greek,
+            Language::Hungarian => Algorithm::Hungarian,
+            Language::Italian => Algorithm::Italian,
+            Language::Norwegian => Algorithm::Norwegian,
+            Language::Portuguese => Algorithm::Portuguese,
+            Language::Romanian => Algorithm::Romanian,
+            Language::Russian => Algorithm::Russian,
+            Language::Spanish => Algorithm::Spanish,
+            Language::Swedish => Algorithm::Swedish,
+            Language::Tamil => Algorithm::Tamil,
+            Language::Turkish => Algorithm::Turkish,
+        }
+    }
+}
+
+impl FromStr for Language {
+    type Err = ();
+
+    /// Parses English names (`german`) and ISO 639-1 codes (`de`).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.to_lowercase();
+
+        LANGUAGES
+            .iter()
+            .find(|x| x.1 == s || x.2 == s)
+            .map(|x| x.0)
+            .ok_or(())
+    }
+}
+
+impl Display for Language {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = LANGUAGES.iter().find(|x| x.0 == *self).unwrap().1;
+        write!(f, "{}", name)
+    }
+}
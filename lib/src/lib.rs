@@ -1,9 +1,26 @@
+pub mod aggregation;
+pub mod analyzer;
+pub mod autocomplete;
+pub mod char_filter;
 pub mod collection;
+pub mod cursor;
 pub mod document;
+pub mod facet;
 pub mod field;
 pub mod file;
+pub mod fuzzy;
+pub mod highlight;
 pub mod inverted_index;
+pub mod language;
+pub mod numeric_index;
+pub mod query;
 pub mod ranker;
 pub mod search;
+pub mod sort;
+pub mod stop_words;
+pub mod suggest;
+pub mod synonym;
 pub mod term;
+pub mod token;
+pub mod token_filter;
 pub mod tokenizer;
@@ -0,0 +1,52 @@
+use std::ops::{Bound, RangeBounds};
+
+use bincode::{Decode, Encode};
+
+/// Values of an int field sorted in ascending order, so ranges are found with a binary search.
+#[derive(Debug, Clone, Default, Encode, Decode)]
+pub struct NumericIndex {
+    values: Vec<(i64, i32)>,
+}
+
+impl NumericIndex {
+    pub fn new() -> NumericIndex {
+        NumericIndex::default()
+    }
+
+    pub fn build(values: impl Iterator<Item = (i64, i32)>) -> NumericIndex {
+        let mut values: Vec<(i64, i32)> = values.collect();
+        values.sort_unstable();
+
+        NumericIndex { values }
+    }
+
+    /// Ids of the documents with values inside the range, ordered by value.
+    pub fn range(&self, range: impl RangeBounds<i64>) -> impl Iterator<Item = i32> + '_ {
+        let start = match range.start_bound() {
+            Bound::Included(x) => self.values.partition_point(|v| v.0 < *x),
+            Bound::Excluded(x) => self.values.partition_point(|v| v.0 <= *x),
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(x) => self.values.partition_point(|v| v.0 <= *x),
+            Bound::Excluded(x) => self.values.partition_point(|v| v.0 < *x),
+            Bound::Unbounded => self.values.len(),
+        };
+
+        self.values[start..end.max(start)].iter().map(|x| x.1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_range() {
+        let index = NumericIndex::build(vec![(30, 0), (10, 1), (50, 2), (20, 3)].into_iter());
+
+        assert_eq!(index.range(10..30).collect::<Vec<i32>>(), vec![1, 3]);
+        assert_eq!(index.range((Bound::Excluded(20), Bound::Unbounded)).collect::<Vec<i32>>(), vec![0, 2]);
+        assert_eq!(index.range(60..).count(), 0);
+    }
+}
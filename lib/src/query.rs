@@ -0,0 +1,581 @@
+use std::fmt::{Display, Formatter};
+use std::iter::Peekable;
+use std::ops::Bound;
+use std::str::Chars;
+
+use regex::{Regex, RegexBuilder};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Occur {
+    /// Document has to match the clause (`+word`, `a AND b`).
+    Must,
+    /// Document may match the clause, matching adds to its score.
+    Should,
+    /// Document must not match the clause (`-word`, `NOT word`).
+    MustNot,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Query {
+    Term(String),
+    /// Terms starting with the text (`prog*`).
+    Prefix(String),
+    /// Terms matching the pattern, `?` is any single character and `*` any number of them (`te?t`, `*ing`).
+    Wildcard(String),
+    /// Terms matching the regular expression as a whole (`/colou?r/`).
+    Regex(String),
+    /// Phrase text and slop, the number of moves allowed between its words.
+    Phrase(String, usize),
+    Boolean(Vec<(Occur, Query)>),
+    /// Query limited to a single field (`title:rust`).
+    Field(String, Box<Query>),
+    /// Int values inside the range (`price>=10`), matches don't add to the score.
+    Range(Bound<i64>, Bound<i64>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QueryError {
+    UnbalancedParenthesis,
+    MissingOperand,
+    UnknownField(String),
+    InvalidNumber(String),
+    InvalidAggregation(String),
+    InvalidCursor,
+    InvalidPattern(String),
+}
+
+impl Display for QueryError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            QueryError::UnbalancedParenthesis => write!(f, "query has unbalanced parentheses"),
+            QueryError::MissingOperand => write!(f, "query operator is missing an operand"),
+            QueryError::UnknownField(name) => write!(f, "there is no field named `{}`", name),
+            QueryError::InvalidNumber(value) => write!(f, "`{}` is not an integer", value),
+            QueryError::InvalidAggregation(name) => {
+                write!(f, "aggregation over `{}` needs an int field and a positive interval", name)
+            }
+            QueryError::InvalidCursor => write!(f, "cursor is malformed or doesn't belong to this sort"),
+            QueryError::InvalidPattern(pattern) => write!(f, "`{}` is not a valid regular expression", pattern),
+        }
+    }
+}
+
+impl std::error::Error for QueryError {}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Lexeme {
+    Open,
+    Close,
+    Plus,
+    Minus,
+    And,
+    Or,
+    Not,
+    Field(String),
+    Compare(&'static str),
+    Word(String),
+    Regex(String),
+    Phrase(String, usize),
+}
+
+const COMPARISONS: [&str; 4] = [">=", "<=", ">", "<"];
+
+/// Splits the query into lexemes, `fields` limits the names a range can be over.
+fn lex(query: &str, fields: Option<&[&str]>) -> Vec<Lexeme> {
+    let mut lexemes = Vec::new();
+    let mut chars = query.chars().peekable();
+    let is_field = |name: &str| fields.map_or(is_field_name(name), |x| x.contains(&name));
+    //unknown names of `name:` clauses are errors rather than terms
+    let is_clause = |name: &str| is_field(name) || is_field_name(name);
+
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {}
+            '(' => lexemes.push(Lexeme::Open),
+            ')' => lexemes.push(Lexeme::Close),
+            '+' | '-' if chars.peek().is_some_and(|x| !x.is_whitespace())
+                && !matches!(lexemes.last(), Some(Lexeme::Compare(_))) => {
+                lexemes.push(if c == '+' { Lexeme::Plus } else { Lexeme::Minus });
+            }
+            '/' if is_regex(&chars) => {
+                let mut pattern = String::new();
+
+                while let Some(x) = chars.next() {
+                    match x {
+                        '/' => break,
+                        '\\' if chars.peek() == Some(&'/') => pattern.push(chars.next().unwrap()),
+                        x => pattern.push(x),
+                    }
+                }
+
+                if !pattern.is_empty() {
+                    lexemes.push(Lexeme::Regex(pattern));
+                }
+            }
+            '"' => {
+                let text: String = chars.by_ref().take_while(|x| *x != '"').collect();
+                let slop = lex_slop(&mut chars);
+
+                if !text.trim().is_empty() {
+                    lexemes.push(Lexeme::Phrase(text, slop));
+                }
+            }
+            c => {
+                let mut word = c.to_string();
+
+                loop {
+                    //`field:/regex/` stops before the regex
+                    let regex = chars.peek() == Some(&'/') && word.strip_suffix(':').is_some_and(is_clause) && {
+                        let mut rest = chars.clone();
+                        rest.next();
+                        is_regex(&rest)
+                    };
+
+                    match chars.next_if(|x| !(x.is_whitespace() || ['(', ')', '"'].contains(x) || regex)) {
+                        Some(x) => word.push(x),
+                        None => break,
+                    }
+                }
+
+                let mut value = word.as_str();
+                //`field:` followed by a group, phrase or regex
+                let is_followed = chars.peek().is_some_and(|x| ['(', '"', '/'].contains(x));
+
+                //`10:30` and URLs stay words
+                let field = value.split_once(':').filter(|x| {
+                    is_clause(x.0) && !x.1.starts_with("//") && (!x.1.is_empty() || is_followed)
+                });
+
+                if let Some((name, rest)) = field {
+                    lexemes.push(Lexeme::Field(name.to_string()));
+                    value = rest;
+                }
+
+                //`price>10`, `price:>10` and `price > 10`, other words with `<` or `>` (`<3`, `->`, `<b>`) stay words
+                if let Some(i) = value.find(['<', '>']) {
+                    let operator = COMPARISONS.iter().find(|x| value[i..].starts_with(**x)).unwrap();
+                    let (name, rest) = (&value[..i], &value[i + operator.len()..]);
+                    let is_number = match rest {
+                        "" => next_word(&chars).parse::<i64>().is_ok(),
+                        rest => rest.parse::<i64>().is_ok(),
+                    };
+                    let previous = match lexemes.last() {
+                        Some(Lexeme::Word(word)) if name.is_empty() && field.is_none() && is_field(word) => Some(word.clone()),
+                        _ => None,
+                    };
+                    let is_range = match name {
+                        "" => field.is_some() || previous.is_some(),
+                        name => field.is_none() && is_field(name),
+                    };
+
+                    if is_range && is_number {
+                        if let Some(previous) = previous {
+                            lexemes.pop();
+                            lexemes.push(Lexeme::Field(previous));
+                        } else if !name.is_empty() {
+                            lexemes.push(Lexeme::Field(name.to_string()));
+                        }
+
+                        lexemes.push(Lexeme::Compare(operator));
+                        value = rest;
+                    }
+                }
+
+                if value.is_empty() {
+                    continue;
+                }
+
+                lexemes.push(match value {
+                    "AND" => Lexeme::And,
+                    "OR" => Lexeme::Or,
+                    "NOT" => Lexeme::Not,
+                    _ => Lexeme::Word(value.to_string()),
+                });
+            }
+        }
+    }
+
+    lexemes
+}
+
+/// Whether the name can be a field of a `name:` clause, which is a word of letters, digits and `_`,
+/// but not only digits (`10:30`).
+fn is_field_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.chars().all(|x| x.is_ascii_digit())
+        && name.chars().all(|x| x.is_alphanumeric() || x == '_')
+}
+
+/// Next word after the whitespace, without consuming it.
+fn next_word(chars: &Peekable<Chars>) -> String {
+    chars
+        .clone()
+        .skip_while(|x| x.is_whitespace())
+        .take_while(|x| !(x.is_whitespace() || ['(', ')', '"'].contains(x)))
+        .collect()
+}
+
+/// Whether the `/` before `chars` starts a regex, which needs a closing `/` at the end of a word,
+/// so paths like `/usr/bin` and `a /b c/d` stay words.
+fn is_regex(chars: &Peekable<Chars>) -> bool {
+    let mut chars = chars.clone();
+
+    while let Some(x) = chars.next() {
+        match x {
+            '\\' if chars.peek() == Some(&'/') => {
+                chars.next();
+            }
+            '/' => return chars.peek().is_none_or(|x| x.is_whitespace() || *x == ')'),
+            _ => {}
+        }
+    }
+
+    false
+}
+
+fn lex_slop(chars: &mut Peekable<Chars>) -> usize {
+    if chars.next_if_eq(&'~').is_none() {
+        return 0;
+    }
+
+    let mut digits = String::new();
+
+    while let Some(x) = chars.next_if(|x| x.is_ascii_digit()) {
+        digits.push(x);
+    }
+
+    digits.parse().unwrap_or_default()
+}
+
+struct Parser {
+    lexemes: Vec<Lexeme>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Lexeme> {
+        self.lexemes.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Lexeme> {
+        let lexeme = self.lexemes.get(self.position).cloned();
+        self.position += 1;
+        lexeme
+    }
+
+    /// Parses clauses until the end of the query or the closing parenthesis.
+    /// `AND` makes clauses on both sides required, `OR` leaves them optional.
+    fn parse_group(&mut self) -> Result<Query, QueryError> {
+        let mut clauses: Vec<(Occur, Query)> = Vec::new();
+        let mut conjunction: Option<Lexeme> = None;
+
+        while let Some(lexeme) = self.peek() {
+            match lexeme {
+                Lexeme::Close => break,
+                Lexeme::And | Lexeme::Or => {
+                    if clauses.is_empty() || conjunction.is_some() {
+                        return Err(QueryError::MissingOperand);
+                    }
+
+                    conjunction = self.next();
+                    continue;
+                }
+                _ => {}
+            }
+
+            let (mut occur, query) = self.parse_clause()?;
+
+            if conjunction == Some(Lexeme::And) {
+                if let Some(last) = clauses.last_mut().filter(|x| x.0 == Occur::Should) {
+                    last.0 = Occur::Must;
+                }
+
+                if occur == Occur::Should {
+                    occur = Occur::Must;
+                }
+            }
+
+            clauses.push((occur, query));
+            conjunction = None;
+        }
+
+        if conjunction.is_some() {
+            return Err(QueryError::MissingOperand);
+        }
+
+        if clauses.len() == 1 && clauses[0].0 == Occur::Should {
+            return Ok(clauses.remove(0).1);
+        }
+
+        Ok(Query::Boolean(clauses))
+    }
+
+    fn parse_clause(&mut self) -> Result<(Occur, Query), QueryError> {
+        let occur = match self.peek() {
+            Some(Lexeme::Plus) => Occur::Must,
+            Some(Lexeme::Minus) | Some(Lexeme::Not) => Occur::MustNot,
+            _ => Occur::Should,
+        };
+
+        if occur != Occur::Should {
+            self.next();
+        }
+
+        Ok((occur, self.parse_operand()?))
+    }
+
+    fn parse_operand(&mut self) -> Result<Query, QueryError> {
+        match self.next() {
+            Some(Lexeme::Open) => {
+                let query = self.parse_group()?;
+
+                if self.next() != Some(Lexeme::Close) {
+                    return Err(QueryError::UnbalancedParenthesis);
+                }
+
+                Ok(query)
+            }
+            Some(Lexeme::Field(name)) => Ok(Query::Field(name, Box::new(self.parse_operand()?))),
+            Some(Lexeme::Compare(operator)) => {
+                let value = match self.next() {
+                    Some(Lexeme::Word(value)) => value,
+                    _ => return Err(QueryError::MissingOperand),
+                };
+                let value = value.parse::<i64>().map_err(|_| QueryError::InvalidNumber(value))?;
+
+                Ok(match operator {
+                    ">=" => Query::Range(Bound::Included(value), Bound::Unbounded),
+                    ">" => Query::Range(Bound::Excluded(value), Bound::Unbounded),
+                    "<=" => Query::Range(Bound::Unbounded, Bound::Included(value)),
+                    _ => Query::Range(Bound::Unbounded, Bound::Excluded(value)),
+                })
+            }
+            Some(Lexeme::Word(word)) => match word.strip_suffix('*') {
+                Some(prefix) if !prefix.is_empty() && !prefix.contains(['*', '?']) => Ok(Query::Prefix(prefix.to_string())),
+                //a trailing `?` is a question mark rather than a wildcard
+                _ if word.trim_end_matches('?').contains(['*', '?']) && word.contains(|x| x != '*' && x != '?') => {
+                    Ok(Query::Wildcard(word))
+                }
+                _ => Ok(Query::Term(word)),
+            },
+            Some(Lexeme::Regex(pattern)) => match Regex::new(&pattern) {
+                Ok(_) => Ok(Query::Regex(pattern)),
+                Err(_) => Err(QueryError::InvalidPattern(pattern)),
+            },
+            Some(Lexeme::Phrase(text, slop)) => Ok(Query::Phrase(text, slop)),
+            _ => Err(QueryError::MissingOperand),
+        }
+    }
+}
+
+impl Query {
+    /// Whether the query is a range, limited to a field or not.
+    pub fn is_range(&self) -> bool {
+        match self {
+            Query::Range(_, _) => true,
+            Query::Field(_, query) => query.is_range(),
+            _ => false,
+        }
+    }
+
+    /// Regular expression matching whole terms of wildcard and regex queries.
+    pub fn pattern(&self, case_insensitive: bool) -> Option<Regex> {
+        let pattern = match self {
+            Query::Wildcard(wildcard) => wildcard
+                .chars()
+                .map(|x| match x {
+                    '*' => ".*".to_string(),
+                    '?' => ".".to_string(),
+                    x => regex::escape(&x.to_string()),
+                })
+                .collect(),
+            Query::Regex(pattern) => pattern.clone(),
+            _ => return None,
+        };
+
+        RegexBuilder::new(&format!("^(?:{})$", pattern))
+            .case_insensitive(case_insensitive)
+            .build()
+            .ok()
+    }
+
+    /// Parses the query language:
+    /// * `word` - single term, terms next to each other are optional (OR)
+    /// * `prefix*` - any term starting with the prefix
+    /// * `te?t`, `*ing` - any term matching the wildcards, `?` is a single character and `*` any number of them
+    /// * `/colou?r/` - any term matching the regular expression as a whole
+    /// * `"some words"` - phrase, optionally followed by `~N` to allow up to N moves between its words
+    /// * `+word`, `-word` - required and excluded clauses
+    /// * `a AND b`, `a OR b`, `NOT a` - boolean operators
+    /// * `(...)` - grouping
+    /// * `field:word`, `field:"some words"`, `field:(...)` - clauses limited to a single field
+    /// * `field>=10`, `field:<50`, `field > 5` - ranges over int fields, they don't change the score
+    ///
+    /// An unterminated quote lasts until the end of the query, a trailing `?` is not a wildcard.
+    /// Words with a colon after something else than a field name (`10:30`, `http://example.com`) are terms,
+    /// as well as comparisons without a field or a number (`<3`, `a->b`).
+    pub fn parse(query: &str) -> Result<Query, QueryError> {
+        Self::parse_lexemes(lex(query, None))
+    }
+
+    /// Parses the query like `parse`, but only the fields are compared with numbers, `I <3 rust` has no range.
+    /// A `name:` clause with any other name is still a field clause, so it can report the unknown field.
+    pub fn parse_with_fields(query: &str, fields: &[&str]) -> Result<Query, QueryError> {
+        Self::parse_lexemes(lex(query, Some(fields)))
+    }
+
+    fn parse_lexemes(lexemes: Vec<Lexeme>) -> Result<Query, QueryError> {
+        let mut parser = Parser {
+            lexemes,
+            position: 0,
+        };
+        let query = parser.parse_group()?;
+
+        if parser.peek().is_some() {
+            return Err(QueryError::UnbalancedParenthesis);
+        }
+
+        Ok(query)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            Query::parse("cheap \"new york\" hotels \"data center\"~3 prog*"),
+            Ok(Query::Boolean(vec![
+                (Occur::Should, Query::Term("cheap".into())),
+                (Occur::Should, Query::Phrase("new york".into(), 0)),
+                (Occur::Should, Query::Term("hotels".into())),
+                (Occur::Should, Query::Phrase("data center".into(), 3)),
+                (Occur::Should, Query::Prefix("prog".into())),
+            ]))
+        );
+    }
+
+    #[test]
+    fn test_parse_boolean() {
+        assert_eq!(
+            Query::parse("+rust -java (async OR tokio) AND NOT well-known"),
+            Ok(Query::Boolean(vec![
+                (Occur::Must, Query::Term("rust".into())),
+                (Occur::MustNot, Query::Term("java".into())),
+                (
+                    Occur::Must,
+                    Query::Boolean(vec![
+                        (Occur::Should, Query::Term("async".into())),
+                        (Occur::Should, Query::Term("tokio".into())),
+                    ])
+                ),
+                (Occur::MustNot, Query::Term("well-known".into())),
+            ]))
+        );
+    }
+
+    #[test]
+    fn test_parse_fields() {
+        assert_eq!(
+            Query::parse("title:rust body:\"async io\" -year:(2021 OR 2022)"),
+            Ok(Query::Boolean(vec![
+                (Occur::Should, Query::Field("title".into(), Box::new(Query::Term("rust".into())))),
+                (Occur::Should, Query::Field("body".into(), Box::new(Query::Phrase("async io".into(), 0)))),
+                (
+                    Occur::MustNot,
+                    Query::Field(
+                        "year".into(),
+                        Box::new(Query::Boolean(vec![
+                            (Occur::Should, Query::Term("2021".into())),
+                            (Occur::Should, Query::Term("2022".into())),
+                        ]))
+                    )
+                ),
+            ]))
+        );
+    }
+
+    #[test]
+    fn test_parse_known_fields() {
+        assert_eq!(
+            Query::parse_with_fields("10:30 title:rust title:(a) note: http://x.com/a/", &["title"]),
+            Ok(Query::Boolean(vec![
+                (Occur::Should, Query::Term("10:30".into())),
+                (Occur::Should, Query::Field("title".into(), Box::new(Query::Term("rust".into())))),
+                (Occur::Should, Query::Field("title".into(), Box::new(Query::Term("a".into())))),
+                (Occur::Should, Query::Term("note:".into())),
+                (Occur::Should, Query::Term("http://x.com/a/".into())),
+            ]))
+        );
+        assert_eq!(Query::parse("10:30"), Ok(Query::Term("10:30".into())));
+        assert_eq!(Query::parse("title:"), Ok(Query::Term("title:".into())));
+        assert_eq!(
+            Query::parse_with_fields("titel:rust", &["title"]),
+            Ok(Query::Field("titel".into(), Box::new(Query::Term("rust".into()))))
+        );
+    }
+
+    #[test]
+    fn test_parse_ranges() {
+        let range = |start, end| Query::Field("price".into(), Box::new(Query::Range(start, end)));
+
+        assert_eq!(
+            Query::parse("price>=10 AND price:<50"),
+            Ok(Query::Boolean(vec![
+                (Occur::Must, range(Bound::Included(10), Bound::Unbounded)),
+                (Occur::Must, range(Bound::Unbounded, Bound::Excluded(50))),
+            ]))
+        );
+        assert_eq!(Query::parse("price > -5"), Ok(range(Bound::Excluded(-5), Bound::Unbounded)));
+        assert_eq!(Query::parse_with_fields("price >=10", &["price"]), Ok(range(Bound::Included(10), Bound::Unbounded)));
+        assert_eq!(Query::parse("price<=ten"), Ok(Query::Term("price<=ten".into())));
+    }
+
+    #[test]
+    fn test_parse_comparison_words() {
+        let terms = |words: &[&str]| Query::Boolean(words.iter().map(|x| (Occur::Should, Query::Term(x.to_string()))).collect());
+        let parse = |query| Query::parse_with_fields(query, &["price"]);
+
+        assert_eq!(parse("I <3 rust"), Ok(terms(&["I", "<3", "rust"])));
+        assert_eq!(parse("C++ > Java"), Ok(terms(&["C++", ">", "Java"])));
+        assert_eq!(parse("price > five"), Ok(terms(&["price", ">", "five"])));
+        assert_eq!(parse("<b>dream</b>"), Ok(Query::Term("<b>dream</b>".into())));
+        assert!(parse("a -> b").is_ok());
+    }
+
+    #[test]
+    fn test_parse_patterns() {
+        assert_eq!(
+            Query::parse("te?t *ing title:/colou?r/ why?"),
+            Ok(Query::Boolean(vec![
+                (Occur::Should, Query::Wildcard("te?t".into())),
+                (Occur::Should, Query::Wildcard("*ing".into())),
+                (Occur::Should, Query::Field("title".into(), Box::new(Query::Regex("colou?r".into())))),
+                (Occur::Should, Query::Term("why?".into())),
+            ]))
+        );
+        assert!(Query::Wildcard("te?t".into()).pattern(true).unwrap().is_match("TEST"));
+        assert!(!Query::Wildcard("te?t".into()).pattern(false).unwrap().is_match("tent!"));
+        assert_eq!(Query::parse("/colo(u/"), Err(QueryError::InvalidPattern("colo(u".into())));
+        assert_eq!(Query::parse("(/a\\/b/)"), Ok(Query::Regex("a/b".into())));
+        assert_eq!(
+            Query::parse("a /b c/d /usr/bin"),
+            Ok(Query::Boolean(vec![
+                (Occur::Should, Query::Term("a".into())),
+                (Occur::Should, Query::Term("/b".into())),
+                (Occur::Should, Query::Term("c/d".into())),
+                (Occur::Should, Query::Term("/usr/bin".into())),
+            ]))
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(Query::parse("(rust"), Err(QueryError::UnbalancedParenthesis));
+        assert_eq!(Query::parse("rust)"), Err(QueryError::UnbalancedParenthesis));
+        assert_eq!(Query::parse("rust AND"), Err(QueryError::MissingOperand));
+        assert_eq!(Query::parse("OR rust"), Err(QueryError::MissingOperand));
+    }
+}
@@ -1,8 +1,10 @@
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap, HashSet};
 
 use crate::collection::Collection;
 use crate::field::{Field, FieldValue};
+use crate::fuzzy;
 use crate::term::Term;
+use crate::token::Token;
 
 pub struct Ranker {}
 
@@ -30,57 +32,36 @@ impl Ranker {
         let b: f32 = 0.75;
         let d: f32 = collection.len() as f32;
 
-        let sum_of_tokens_count: usize = collection
-            .iter()
-            .map(|(_id, doc)| {
-                let field_value = doc.get(field.name.as_str()).unwrap();
-
-                if let FieldValue::String(_, tokens) = field_value {
-                    if let Some(tokens) = tokens {
-                        return tokens.len()
-                    }
-                }
-                0
-            })
-            .sum();
-
+        let sum_of_tokens_count = field.inverted_index.tokens_count();
         let avgdl: f32 = sum_of_tokens_count as f32 / d;
         let mut ranks = HashMap::new();
 
-        for (id, doc) in collection.iter() {
-            let field_value = doc.get(field.name.as_str()).unwrap();
+        let postings = match field.inverted_index.get(term) {
+            Some(postings) => postings,
+            None => return ranks,
+        };
 
-            if let FieldValue::String(_, tokens) = field_value {
-                if let Some(tokens) = tokens {
-                    let freq: f32 = tokens
-                        .iter()
-                        .filter(|x| *x == term)
-                        .count() as f32;
-                    let bm25: f32 =
-                        idf * ((freq * (k1 + 1f32)) / (freq + k1 * (1f32 - b + b * (d / avgdl))));
+        for (id, positions) in postings {
+            let freq: f32 = positions.len() as f32;
+            let bm25: f32 =
+                idf * ((freq * (k1 + 1f32)) / (freq + k1 * (1f32 - b + b * (d / avgdl))));
 
-                    if bm25 == 0f32 {
-                        continue;
-                    }
-
-                    ranks.insert(*id, bm25);
-                }
+            if bm25 == 0f32 {
+                continue;
             }
+
+            ranks.insert(*id, bm25);
         }
 
         ranks
     }
 
-    pub fn rank_int(term: &Term, _strict: bool, collection: &Collection, field: &Field) -> HashMap<i32, f32> {
+    pub fn rank_int(term: &Term, _strict: bool, _collection: &Collection, field: &Field) -> HashMap<i32, f32> {
         let mut ranks = HashMap::new();
 
-        for (id, doc) in collection.iter() {
-            let field_value = doc.get(field.name.as_str()).unwrap();
-
-            if let FieldValue::Int(value) = field_value {
-                if value.to_string() == term.value {
-                    ranks.insert(*id, 1f32);
-                }
+        if let Ok(value) = term.value.parse::<i64>() {
+            for id in field.numeric_index.range(value..=value) {
+                ranks.insert(id, 1f32);
             }
         }
 
@@ -123,11 +104,195 @@ impl Ranker {
         ranks
     }
 
+    /// Documents with exactly the value, looked up in the index.
+    pub fn rank_keyword(term: &Term, _strict: bool, _collection: &Collection, field: &Field) -> HashMap<i32, f32> {
+        match field.inverted_index.get(term) {
+            Some(postings) => postings.keys().map(|id| (*id, 1f32)).collect(),
+            None => HashMap::new(),
+        }
+    }
+
+    /// Distance of the closest match of the phrase inside the document, `None` if a token is missing.
+    /// Every token of the match takes a position of its own, and the distance is how far apart the tokens are
+    /// from their places in the phrase (the spread of `position - offset`), so `0` is an exact match
+    /// and swapping two words takes 2 moves.
+    fn phrase_distance(tokens: &[Token], field: &Field, id: i32) -> Option<usize> {
+        let first = tokens.iter().map(|x| x.position).min()?;
+        let mut offsets = Vec::new();
+
+        for token in tokens {
+            let positions = field.inverted_index.positions(&token.term, id)?;
+            offsets.push((token, (token.position - first) as isize, positions));
+        }
+
+        //every match starts where one of its tokens is, minus the offset of the token
+        let starts: BTreeSet<isize> = offsets
+            .iter()
+            .flat_map(|(_, offset, positions)| positions.iter().map(move |x| *x as isize - offset))
+            .collect();
+
+        starts
+            .into_iter()
+            .filter_map(|start| Self::phrase_match(&offsets, start))
+            .min()
+    }
+
+    /// Distance of the match starting at `start`, every token takes its first free position at or after its place.
+    /// Repeated tokens take different positions, so `new new york` doesn't match a single `new`.
+    fn phrase_match(offsets: &[(&Token, isize, &Vec<usize>)], start: isize) -> Option<usize> {
+        let mut taken: HashSet<(&Term, usize)> = HashSet::new();
+        let mut end = start;
+
+        for (token, offset, positions) in offsets {
+            let i = positions.partition_point(|x| (*x as isize) < start + offset);
+            let position = positions[i..].iter().find(|x| !taken.contains(&(&token.term, **x)))?;
+
+            taken.insert((&token.term, *position));
+            end = end.max(*position as isize - offset);
+        }
+
+        Some((end - start) as usize)
+    }
+
+    /// Ranks documents which contain all the tokens with at most `slop` moves between them
+    /// (`0` means the tokens have to be next to each other and in the same order).
+    /// The score of a match is the sum of BM25 scores of its terms, boosted by how close the terms are,
+    /// so an exact phrase gets twice the score of the same words scattered around the field.
+    pub fn rank_phrase(tokens: &[Token], slop: usize, strict: bool, collection: &Collection, field: &Field) -> HashMap<i32, f32> {
+        let mut ranks = HashMap::new();
+
+        //grams of a word may repeat at the same position
+        let mut unique: Vec<Token> = Vec::new();
+
+        for token in tokens {
+            if !unique.iter().any(|x| x.term == token.term && x.position == token.position) {
+                unique.push(token.clone());
+            }
+        }
+
+        let tokens = unique.as_slice();
+
+        if tokens.len() == 1 {
+            return Self::rank(&tokens[0].term, strict, collection, field);
+        }
+
+        if !matches!(field.value, FieldValue::String(_, _) | FieldValue::Keyword(_)) || tokens.is_empty() {
+            return ranks;
+        }
+
+        let postings = match field.inverted_index.get(&tokens[0].term) {
+            Some(postings) => postings,
+            None => return ranks,
+        };
+
+        for id in postings.keys() {
+            let distance = Self::phrase_distance(tokens, field, *id);
+
+            if let Some(distance) = distance.filter(|x| *x <= slop) {
+                ranks.insert(*id, 1f32 + 1f32 / (1f32 + distance as f32));
+            }
+        }
+
+        let mut scores: HashMap<i32, f32> = HashMap::new();
+
+        for token in tokens {
+            for (id, rank) in Self::bm25(&token.term, collection, field) {
+                *scores.entry(id).or_default() += rank;
+            }
+        }
+
+        for (id, rank) in ranks.iter_mut() {
+            *rank *= scores.get(id).copied().unwrap_or_default();
+        }
+
+        ranks
+    }
+
+    /// Ranks documents of a string field by the terms within the allowed typos from the term.
+    /// Every edit lowers the score, and a document scores as its best matching term.
+    pub fn rank_fuzzy(term: &Term, collection: &Collection, field: &Field) -> HashMap<i32, f32> {
+        if !matches!(field.value, FieldValue::String(_, _)) {
+            return Self::rank(term, false, collection, field);
+        }
+
+        let mut ranks: HashMap<i32, f32> = HashMap::new();
+
+        for (term, edits) in fuzzy::expand(&field.inverted_index, term) {
+            for (id, rank) in Self::bm25(term, collection, field) {
+                let e = ranks.entry(id).or_default();
+                *e = e.max(rank / (1f32 + edits as f32));
+            }
+        }
+
+        ranks
+    }
+
     pub fn rank(term: &Term, strict: bool, collection: &Collection, field: &Field) -> HashMap<i32, f32> {
         match field.value {
             FieldValue::Int(_) => Self::rank_int(term, strict, collection, field),
             FieldValue::Bool(_) => Self::rank_bool(term, strict, collection, field),
             FieldValue::String(_, _) => Self::rank_string(term, strict, collection, field),
+            FieldValue::Keyword(_) => Self::rank_keyword(term, strict, collection, field),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::Document;
+    use crate::tokenizer::tokenize_with_positions;
+
+    fn collection(texts: &[&str]) -> Collection {
+        let mut collection = Collection::new();
+        collection.push_field("title", FieldValue::String("".into(), None));
+
+        for text in texts {
+            let mut doc = Document::new();
+            doc.push("title", FieldValue::String(text.to_string(), None));
+            collection.push(doc, None);
+        }
+
+        collection.commit();
+        collection
+    }
+
+    fn phrase(collection: &Collection, text: &str, slop: usize) -> Vec<i32> {
+        let tokens = tokenize_with_positions(text);
+        let mut ids: Vec<i32> = Ranker::rank_phrase(&tokens, slop, false, collection, &collection.fields[0])
+            .into_keys()
+            .collect();
+
+        ids.sort();
+        ids
+    }
+
+    #[test]
+    fn test_rank_phrase() {
+        let collection = collection(&["new york city", "york is new", "new big york", "new and new york"]);
+
+        assert_eq!(phrase(&collection, "new york", 0), vec![0, 3]);
+        assert_eq!(phrase(&collection, "new york", 1), vec![0, 2, 3]);
+        //swapped words take 2 moves, the gap one more
+        assert_eq!(phrase(&collection, "new york", 2), vec![0, 2, 3]);
+        assert_eq!(phrase(&collection, "new york", 3), vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn test_rank_phrase_repeated() {
+        let collection = collection(&["new york city", "new and new york"]);
+
+        assert_eq!(phrase(&collection, "new new york", 0), Vec::<i32>::new());
+        assert_eq!(phrase(&collection, "new new york", 1), vec![1]);
+        assert_eq!(phrase(&collection, "new new york", 10), vec![1]);
+    }
+
+    #[test]
+    fn test_rank_phrase_score() {
+        let collection = collection(&["new york city", "new big york"]);
+        let tokens = tokenize_with_positions("new york");
+        let ranks = Ranker::rank_phrase(&tokens, 1, false, &collection, &collection.fields[0]);
+
+        assert!(ranks[&0] > ranks[&1]);
+    }
+}
@@ -1,52 +1,963 @@
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap, HashSet};
 
+use crate::aggregation::{Aggregation, AggregationResult};
+use crate::analyzer::AnalysisMode;
 use crate::collection::Collection;
-use crate::field::Field;
+use crate::cursor::Cursor;
+use crate::facet::Facet;
+use crate::field::{Field, FieldValue};
+use crate::fuzzy;
+use crate::highlight::{Highlight, HighlightOptions};
+use crate::query::{Occur, Query, QueryError};
 use crate::ranker::Ranker;
-use crate::tokenizer::tokenize;
+use crate::sort::{Sort, SortBy, SortValue};
+use crate::term::Term;
+
+/// Synonym matches score this much of an exact match.
+const SYNONYM_BOOST: f32 = 0.9;
+
+#[derive(Debug, Clone, Default)]
+pub struct SearchOptions {
+    pub strict: bool,
+    /// Fields to search in, all fields by default.
+    pub fields: Option<Vec<String>>,
+    /// Number of hits to skip.
+    pub offset: usize,
+    /// Maximum number of hits, 10 by default.
+    pub limit: Option<usize>,
+    /// Cursor of the last hit of the previous page, the hits start right after it.
+    pub search_after: Option<String>,
+    /// Query which restricts the matching documents without changing their score.
+    pub filter: Option<String>,
+    /// Fields to count the values of among all matching documents.
+    pub facets: Vec<String>,
+    /// Maximum number of values per facet, 10 by default.
+    pub facet_size: Option<usize>,
+    /// Named aggregations over int fields of all matching documents.
+    pub aggregations: HashMap<String, Aggregation>,
+    /// Order of the hits, relevance is always the last tie-breaker.
+    pub sort: Vec<Sort>,
+    /// Highlight matched terms of string fields in the hits.
+    pub highlight: Option<HighlightOptions>,
+    /// Maximum number of terms a wildcard or regex pattern expands to, the most frequent are kept. 50 by default.
+    pub max_expansions: Option<usize>,
+    /// Match terms of string fields with typos, the more edits the lower the score.
+    pub typo_tolerance: bool,
+    /// Suggest corrected queries when there are fewer matching documents, 1 by default so only queries without any get them.
+    /// `Some(0)` doesn't suggest anything.
+    /// Suggesting compares the unknown words with the whole dictionary of the fields.
+    pub suggest_below: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hit {
+    pub id: i32,
+    pub score: f32,
+    /// Highlighted string fields which have matched terms.
+    pub highlights: HashMap<String, Highlight>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SearchResult {
+    pub hits: Vec<Hit>,
+    /// Number of all matching documents.
+    pub total: usize,
+    /// Cursor of the last hit, to be passed as `search_after` for the next page.
+    pub cursor: Option<String>,
+    pub facets: HashMap<String, Vec<Facet>>,
+    pub aggregations: HashMap<String, AggregationResult>,
+    /// Corrected queries, when the query has few matching documents.
+    pub suggestions: Vec<String>,
+}
 
 impl Collection {
-    pub fn search(
-        &self,
-        query: &str,
-        strict: bool,
-        fields: Option<Vec<String>>,
-        max: Option<usize>,
-    ) -> Vec<(i32, f32)> {
-        let max = max.unwrap_or(10);
+    pub fn search(&self, query: &str, options: &SearchOptions) -> Result<SearchResult, QueryError> {
+        let limit = options.limit.unwrap_or(10);
+
+        let query_text = query;
+        let names: Vec<&str> = self.fields.iter().map(|x| x.name.as_str()).collect();
+        let query = Query::parse_with_fields(query, &names)?;
+        let filter = options
+            .filter
+            .as_deref()
+            .map(|x| Query::parse_with_fields(x, &names))
+            .transpose()?;
+
+        let fields = match &options.fields {
+            Some(fields) => self.get_fields(fields)?,
+            None => self.fields.iter().collect(),
+        };
+        let facet_fields = self.get_fields(&options.facets)?;
+        let mut sort = options.sort.clone();
+
+        for by in sort.iter().map(|x| &x.by) {
+            if let SortBy::Field(name) = by {
+                self.get_field(name).ok_or_else(|| QueryError::UnknownField(name.clone()))?;
+            }
+        }
 
-        let terms = tokenize(query);
+        if !sort.iter().any(|x| x.by == SortBy::Score) {
+            sort.push(Sort::score());
+        }
+
+        let search_after = match &options.search_after {
+            Some(cursor) => {
+                let cursor = Cursor::decode(cursor).filter(|x| x.values.len() == sort.len());
+                Some(cursor.ok_or(QueryError::InvalidCursor)?)
+            }
+            None => None,
+        };
+        let aggregations = options
+            .aggregations
+            .iter()
+            .map(|(name, aggregation)| Ok((name, aggregation, self.get_aggregation_field(aggregation)?)))
+            .collect::<Result<Vec<_>, QueryError>>()?;
+
+        let mut docs = self.evaluate(&query, options, false, &fields)?;
+
+        //filter only restricts the documents, its matches don't add to the score
+        if let Some(filter) = filter {
+            let all_fields: Vec<&Field> = self.fields.iter().collect();
+            let filtered = self.evaluate(&filter, options, true, &all_fields)?;
+
+            docs.retain(|id, _| filtered.contains_key(id));
+        }
+
+        let facets = facet_fields
+            .into_iter()
+            .map(|field| {
+                let facets = Facet::count(self, field, docs.keys(), options.facet_size.unwrap_or(10));
+                (field.name.clone(), facets)
+            })
+            .collect();
+
+        let aggregations = aggregations
+            .into_iter()
+            .map(|(name, aggregation, field)| (name.clone(), aggregation.aggregate(self, field, docs.keys())))
+            .collect();
+
+        let mut hits: Vec<(Vec<SortValue>, Hit)> = docs
+            .into_iter()
+            .map(|(id, score)| {
+                let hit = Hit {
+                    id,
+                    score,
+                    highlights: HashMap::new(),
+                };
+                (Sort::values(&sort, self, &hit), hit)
+            })
+            .collect();
+
+        hits.sort_by(|x, y| Sort::compare_all(&sort, (&x.0, x.1.id), (&y.0, y.1.id)));
+
+        let total = hits.len();
+        let suggestions = match options.suggest_below.unwrap_or(1) {
+            below if total < below => self.suggest(query_text, &fields, 3),
+            _ => Vec::new(),
+        };
+        let start = match search_after {
+            Some(cursor) => hits.partition_point(|x| {
+                Sort::compare_all(&sort, (&x.0, x.1.id), (&cursor.values, cursor.id)).is_le()
+            }),
+            None => 0,
+        };
 
-        let fields = match fields {
-            Some(fields) => {
-                let fields: Vec<&Field> =
-                    fields.iter().map(|x| self.get_field(x).unwrap()).collect();
+        let mut hits: Vec<(Vec<SortValue>, Hit)> = hits
+            .into_iter()
+            .skip(start.saturating_add(options.offset))
+            .take(limit)
+            .collect();
 
-                fields
+        let cursor = hits.last_mut().map(|x| {
+            Cursor {
+                values: std::mem::take(&mut x.0),
+                id: x.1.id,
             }
+            .encode()
+        });
+        let mut hits: Vec<Hit> = hits.into_iter().map(|x| x.1).collect();
+
+        if let Some(highlight) = &options.highlight {
+            self.highlight(&query, &fields, options, highlight, &mut hits)?;
+        }
+
+        Ok(SearchResult {
+            hits,
+            total,
+            cursor,
+            facets,
+            aggregations,
+            suggestions,
+        })
+    }
+
+    fn highlight(
+        &self,
+        query: &Query,
+        fields: &[&Field],
+        search_options: &SearchOptions,
+        options: &HighlightOptions,
+        hits: &mut [Hit],
+    ) -> Result<(), QueryError> {
+        let mut terms = HashMap::new();
+        self.matched_terms(query, search_options, fields, &mut terms);
+
+        let fields = match &options.fields {
+            Some(fields) => self.get_fields(fields)?,
             None => self.fields.iter().collect(),
         };
 
-        let mut docs: HashMap<i32, f32> = HashMap::new();
+        for hit in hits {
+            let doc = match self.get(hit.id) {
+                Some(doc) => doc,
+                None => continue,
+            };
 
-        for term in &terms {
             for field in &fields {
-                let ranks = Ranker::rank(term, strict, self, field);
+                let terms = match terms.get(&field.name) {
+                    Some(terms) => terms,
+                    None => continue,
+                };
+
+                if let Some(FieldValue::String(text, Some(tokens))) = doc.get(field.name.as_str()) {
+                    if let Some(highlight) = Highlight::new(text, tokens, terms, options) {
+                        hit.highlights.insert(field.name.clone(), highlight);
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Terms of the clauses documents have to or may match, grouped by field name.
+    fn matched_terms(&self, query: &Query, options: &SearchOptions, fields: &[&Field], terms: &mut HashMap<String, HashSet<Term>>) {
+        if let Query::Term(text) | Query::Phrase(text, _) = query {
+            self.matched_synonyms(text, fields, terms);
+        }
+
+        match query {
+            Query::Term(text) if options.typo_tolerance && !options.strict => {
+                for field in fields {
+                    let expanded = self
+                        .analyze(field, text, AnalysisMode::Query)
+                        .iter()
+                        .flat_map(|token| fuzzy::expand(&field.inverted_index, &token.term))
+                        .map(|(term, _)| term.clone())
+                        .collect::<Vec<Term>>();
+
+                    terms.entry(field.name.clone()).or_default().extend(expanded);
+                }
+            }
+            Query::Term(text) | Query::Phrase(text, _) => {
+                let mode = match query {
+                    Query::Phrase(_, _) => AnalysisMode::Phrase,
+                    _ => AnalysisMode::Query,
+                };
+
+                for field in fields {
+                    let tokens = self
+                        .analyze(field, text, mode)
+                        .into_iter()
+                        .flat_map(|x| self.expand_gram_word(field, &x.term.value).unwrap_or_else(|| vec![x.term]));
+                    terms.entry(field.name.clone()).or_default().extend(tokens);
+                }
+            }
+            Query::Prefix(_) | Query::Wildcard(_) | Query::Regex(_) => {
+                for field in fields {
+                    terms.entry(field.name.clone()).or_default().extend(self.expand(query, options, field));
+                }
+            }
+            Query::Boolean(clauses) => {
+                for (occur, query) in clauses {
+                    if *occur != Occur::MustNot {
+                        self.matched_terms(query, options, fields, terms);
+                    }
+                }
+
+                for run in Self::term_runs(clauses) {
+                    self.matched_synonyms(&run, fields, terms);
+                }
+            }
+            Query::Field(name, query) => {
+                if let Some(field) = self.get_field(name) {
+                    self.matched_terms(query, options, &[field], terms);
+                }
+            }
+            Query::Range(_, _) => {}
+        }
+    }
+
+    fn matched_synonyms(&self, text: &str, fields: &[&Field], terms: &mut HashMap<String, HashSet<Term>>) {
+        for field in fields {
+            let synonyms = self
+                .synonyms_of(field, text)
+                .into_iter()
+                .flat_map(|x| self.analyze(field, x, AnalysisMode::Phrase));
+            terms.entry(field.name.clone()).or_default().extend(synonyms.map(|x| x.term));
+        }
+    }
+
+    /// Texts of the runs of two or more adjacent optional terms (`new york`), which multi word synonyms are matched against.
+    fn term_runs(clauses: &[(Occur, Query)]) -> Vec<String> {
+        let mut runs = Vec::new();
+
+        for group in clauses.split(|(occur, query)| *occur != Occur::Should || !matches!(query, Query::Term(_))) {
+            let words: Vec<&str> = group
+                .iter()
+                .filter_map(|(_, query)| match query {
+                    Query::Term(text) => Some(text.as_str()),
+                    _ => None,
+                })
+                .collect();
+
+            for start in 0..words.len() {
+                for end in start + 2..=words.len() {
+                    runs.push(words[start..end].join(" "));
+                }
+            }
+        }
+
+        runs
+    }
+
+    fn get_aggregation_field(&self, aggregation: &Aggregation) -> Result<&Field, QueryError> {
+        let name = aggregation.field_name();
+        let field = self
+            .get_field(name)
+            .ok_or_else(|| QueryError::UnknownField(name.clone()))?;
+
+        let is_valid = match aggregation {
+            Aggregation::Stats(_) => true,
+            Aggregation::Histogram(_, interval) => *interval > 0,
+        };
+
+        if !is_valid || !matches!(field.value, FieldValue::Int(_)) {
+            return Err(QueryError::InvalidAggregation(name.clone()));
+        }
+
+        Ok(field)
+    }
+
+    pub(crate) fn get_fields(&self, names: &[String]) -> Result<Vec<&Field>, QueryError> {
+        names
+            .iter()
+            .map(|x| self.get_field(x).ok_or_else(|| QueryError::UnknownField(x.clone())))
+            .collect()
+    }
+
+    /// Terms of the string field whose words start with the prefix, so `dreami*` finds `dreaming` indexed as `dream`.
+    /// Keyword fields are case-sensitive.
+    fn expand_prefix(&self, field: &Field, prefix: &str) -> Vec<Term> {
+        match field.value {
+            FieldValue::String(_, _) => {
+                let prefix = self.normalize_word(field, prefix);
+                let terms: BTreeSet<&Term> = field.inverted_index.words(&prefix).map(|(_, word)| &word.term).collect();
+
+                terms.into_iter().cloned().collect()
+            }
+            FieldValue::Keyword(_) => field.inverted_index.prefix(prefix).map(|(term, _)| term.clone()).collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Terms of the n-gram field whose words contain the query word, or start with it for edge grams.
+    /// `None` for other fields and for words of the grams size, which are terms of the index themselves.
+    fn expand_gram_word(&self, field: &Field, word: &str) -> Option<Vec<Term>> {
+        let config = &field.analyzer;
+        let edge = match config.name.as_str() {
+            "ngram" => false,
+            "edge_ngram" => true,
+            _ => return None,
+        };
+        let length = word.chars().count();
 
-                for rank in &ranks {
-                    let e = docs.entry(*rank.0);
-                    *e.or_default() += rank.1;
+        if !matches!(field.value, FieldValue::String(_, _)) || (config.min_gram.max(1)..=config.max_gram).contains(&length) {
+            return None;
+        }
+
+        let index = &field.inverted_index;
+        let terms: BTreeSet<&Term> = match edge {
+            true => index.words(word).map(|(_, x)| &x.term).collect(),
+            false => index.words("").filter(|(x, _)| x.contains(word)).map(|(_, x)| &x.term).collect(),
+        };
+
+        Some(terms.into_iter().cloned().collect())
+    }
+
+    /// Documents matching one of the terms of every word, a word scores as its best matching term.
+    fn rank_words(&self, words: Vec<Vec<Term>>, strict: bool, field: &Field) -> HashMap<i32, f32> {
+        let mut docs: Option<HashMap<i32, f32>> = None;
+
+        for terms in words {
+            let mut ranks: HashMap<i32, f32> = HashMap::new();
+
+            for term in &terms {
+                for (id, rank) in Ranker::rank(term, strict, self, field) {
+                    let e = ranks.entry(id).or_default();
+                    *e = e.max(rank);
                 }
             }
+
+            docs = Some(match docs {
+                Some(mut docs) => {
+                    docs.retain(|id, _| ranks.contains_key(id));
+                    docs.iter_mut().for_each(|(id, score)| *score += ranks[id]);
+                    docs
+                }
+                None => ranks,
+            });
+        }
+
+        docs.unwrap_or_default()
+    }
+
+    /// Terms of the string field whose words match the wildcard or regex query, the most frequent ones if there are too many.
+    /// Words are matched before stemming, lowercased and folded to ASCII, keyword fields are case-sensitive.
+    fn expand_pattern(field: &Field, query: &Query, max_expansions: usize) -> Vec<Term> {
+        let keyword = matches!(field.value, FieldValue::Keyword(_));
+        let pattern = match query.pattern(!keyword) {
+            Some(pattern) if keyword || matches!(field.value, FieldValue::String(_, _)) => pattern,
+            _ => return Vec::new(),
+        };
+
+        //matching terms share the literal start of the wildcard, so only that part of the dictionary is scanned
+        let start = match query {
+            Query::Wildcard(wildcard) => wildcard[..wildcard.find(['*', '?']).unwrap_or(wildcard.len())].to_string(),
+            _ => String::new(),
+        };
+        let start = if keyword { start } else { start.to_lowercase() };
+
+        let index = &field.inverted_index;
+        let mut terms: Vec<(&Term, usize)> = match keyword {
+            true => index
+                .prefix(&start)
+                .filter(|(term, _)| pattern.is_match(&term.value))
+                .map(|(term, postings)| (term, postings.len()))
+                .collect(),
+            false => {
+                let terms: BTreeSet<&Term> = index
+                    .words(&start)
+                    .filter(|(word, _)| pattern.is_match(word))
+                    .map(|(_, word)| &word.term)
+                    .collect();
+
+                terms.into_iter().map(|x| (x, index.get(x).map_or(0, |x| x.len()))).collect()
+            }
+        };
+
+        terms.sort_by_key(|x| std::cmp::Reverse(x.1));
+        terms.into_iter().take(max_expansions).map(|x| x.0.clone()).collect()
+    }
+
+    /// Terms of the field the prefix, wildcard or regex query stands for.
+    fn expand(&self, query: &Query, options: &SearchOptions, field: &Field) -> Vec<Term> {
+        match query {
+            Query::Prefix(prefix) => self.expand_prefix(field, prefix),
+            _ => Self::expand_pattern(field, query, options.max_expansions.unwrap_or(50)),
         }
+    }
 
-        let mut sorted_docs: Vec<_> = docs.into_iter().collect();
-        sorted_docs.sort_by(|x, y| y.1.total_cmp(&x.1));
+    /// Ids of the documents whose field value is exactly `text`, all with zero score.
+    fn filter_value(&self, text: &str, field: &Field) -> HashMap<i32, f32> {
+        let value = match field.value {
+            FieldValue::Int(_) => match text.parse::<i64>() {
+                Ok(value) => FieldValue::Int(value),
+                Err(_) => return HashMap::new(),
+            },
+            FieldValue::Bool(_) => match text.parse::<bool>() {
+                Ok(value) => FieldValue::Bool(value),
+                Err(_) => return HashMap::new(),
+            },
+            FieldValue::String(_, _) => FieldValue::String(text.to_string(), None),
+            FieldValue::Keyword(_) => return Ranker::rank_keyword(&text.into(), true, self, field)
+                .into_keys()
+                .map(|id| (id, 0f32))
+                .collect(),
+        };
 
-        if sorted_docs.len() > max {
-            return sorted_docs[0..max].to_vec();
+        if let FieldValue::Int(value) = value {
+            return field.numeric_index.range(value..=value).map(|id| (id, 0f32)).collect();
         }
 
-        sorted_docs
+        self.iter()
+            .filter(|(_, doc)| doc.get(field.name.as_str()) == Some(&value))
+            .map(|(id, _)| (*id, 0f32))
+            .collect()
+    }
+
+    /// Adds documents matching the synonyms of the text, scored slightly below exact matches.
+    /// A document matching both the text and its synonyms keeps the better score.
+    fn rank_synonyms(&self, text: &str, strict: bool, fields: &[&Field], docs: &mut HashMap<i32, f32>) {
+        for field in fields {
+            for entry in self.synonyms_of(field, text) {
+                let tokens = self.analyze(field, entry, AnalysisMode::Phrase);
+                //an exact phrase doubles the sum of its terms scores, a multi word entry scores as its average term instead
+                let weight = match tokens.len() {
+                    0 | 1 => SYNONYM_BOOST,
+                    n => SYNONYM_BOOST / (2 * n) as f32,
+                };
+
+                for (id, rank) in Ranker::rank_phrase(&tokens, 0, strict, self, field) {
+                    let e = docs.entry(id).or_default();
+                    *e = e.max(rank * weight);
+                }
+            }
+        }
+    }
+
+    /// Ids of the documents matching the query with their scores.
+    /// In `filter` mode terms and phrases match whole field values exactly and nothing is scored.
+    fn evaluate(&self, query: &Query, options: &SearchOptions, filter: bool, fields: &[&Field]) -> Result<HashMap<i32, f32>, QueryError> {
+        let strict = options.strict;
+        let mut docs: HashMap<i32, f32> = HashMap::new();
+
+        match query {
+            Query::Term(text) | Query::Phrase(text, _) if filter => {
+                for field in fields {
+                    docs.extend(self.filter_value(text, field));
+                }
+            }
+            Query::Prefix(prefix) if filter => {
+                for field in fields {
+                    docs.extend(self.iter().filter_map(|(id, doc)| match doc.get(field.name.as_str()) {
+                        Some(FieldValue::String(value, _) | FieldValue::Keyword(value)) if value.starts_with(prefix.as_str()) => {
+                            Some((*id, 0f32))
+                        }
+                        _ => None,
+                    }));
+                }
+            }
+            Query::Wildcard(_) | Query::Regex(_) if filter => {
+                //patterns are validated by the parser
+                let pattern = query.pattern(false).unwrap();
+
+                for field in fields {
+                    docs.extend(self.iter().filter_map(|(id, doc)| match doc.get(field.name.as_str()) {
+                        Some(FieldValue::String(value, _) | FieldValue::Keyword(value)) if pattern.is_match(value) => Some((*id, 0f32)),
+                        _ => None,
+                    }));
+                }
+            }
+            Query::Prefix(_) | Query::Wildcard(_) | Query::Regex(_) => {
+                //a document scores as its best matching term, so common patterns don't pile up
+                for field in fields {
+                    let mut ranks: HashMap<i32, f32> = HashMap::new();
+
+                    for term in self.expand(query, options, field) {
+                        for (id, rank) in Ranker::rank(&term, false, self, field) {
+                            let e = ranks.entry(id).or_default();
+                            *e = e.max(rank);
+                        }
+                    }
+
+                    for rank in ranks {
+                        *docs.entry(rank.0).or_default() += rank.1;
+                    }
+                }
+            }
+            Query::Term(text) => {
+                for field in fields {
+                    let tokens = self.analyze(field, text, AnalysisMode::Query);
+                    let expanded: Vec<Option<Vec<Term>>> =
+                        tokens.iter().map(|x| self.expand_gram_word(field, &x.term.value)).collect();
+
+                    //a word analyzed into several terms (CJK bigrams, `e-mail`) only matches with all of them in place
+                    let ranks = match tokens.as_slice() {
+                        _ if expanded.iter().any(Option::is_some) => {
+                            let words = tokens
+                                .iter()
+                                .zip(expanded)
+                                .map(|(token, terms)| terms.unwrap_or_else(|| vec![token.term.clone()]))
+                                .collect();
+                            self.rank_words(words, strict, field)
+                        }
+                        [token] if options.typo_tolerance && !strict => Ranker::rank_fuzzy(&token.term, self, field),
+                        tokens => Ranker::rank_phrase(tokens, 0, strict, self, field),
+                    };
+
+                    for rank in ranks {
+                        *docs.entry(rank.0).or_default() += rank.1;
+                    }
+                }
+
+                self.rank_synonyms(text, strict, fields, &mut docs);
+            }
+            Query::Phrase(text, slop) => {
+                for field in fields {
+                    let tokens = self.analyze(field, text, AnalysisMode::Phrase);
+
+                    for rank in Ranker::rank_phrase(&tokens, *slop, strict, self, field) {
+                        *docs.entry(rank.0).or_default() += rank.1;
+                    }
+                }
+
+                self.rank_synonyms(text, strict, fields, &mut docs);
+            }
+            Query::Boolean(clauses) => {
+                //next to scoring clauses optional ranges filter their documents rather than add to them
+                let scoring = clauses.iter().any(|(occur, query)| *occur != Occur::MustNot && !query.is_range());
+                let mut results: Vec<(Occur, HashMap<i32, f32>)> = Vec::new();
+                let mut ranges: Vec<HashMap<i32, f32>> = Vec::new();
+
+                for (occur, query) in clauses {
+                    let ranks = self.evaluate(query, options, filter, fields)?;
+
+                    match occur {
+                        Occur::Should if scoring && query.is_range() => ranges.push(ranks),
+                        occur => results.push((*occur, ranks)),
+                    }
+                }
+
+                //the words of a multi word synonym are separate clauses, their synonyms are one more optional clause
+                if !filter {
+                    for run in Self::term_runs(clauses) {
+                        let mut ranks = HashMap::new();
+                        self.rank_synonyms(&run, strict, fields, &mut ranks);
+
+                        if !ranks.is_empty() {
+                            results.push((Occur::Should, ranks));
+                        }
+                    }
+                }
+
+                let has_must = results.iter().any(|x| x.0 == Occur::Must);
+                let has_should = results.iter().any(|x| x.0 == Occur::Should);
+
+                //documents have to match every required clause, or any optional one if there are none
+                //a query with excluded clauses only matches everything else
+                if has_must {
+                    let mut musts = results.iter().filter(|x| x.0 == Occur::Must);
+                    docs = musts.next().unwrap().1.clone();
+
+                    for (_, ranks) in musts {
+                        docs.retain(|id, _| ranks.contains_key(id));
+
+                        for (id, rank) in docs.iter_mut() {
+                            *rank += ranks[id];
+                        }
+                    }
+
+                    for (_, ranks) in results.iter().filter(|x| x.0 == Occur::Should) {
+                        for (id, rank) in docs.iter_mut() {
+                            *rank += ranks.get(id).copied().unwrap_or_default();
+                        }
+                    }
+                } else if has_should {
+                    for (_, ranks) in results.iter().filter(|x| x.0 == Occur::Should) {
+                        for rank in ranks {
+                            *docs.entry(*rank.0).or_default() += rank.1;
+                        }
+                    }
+                } else if !results.is_empty() {
+                    docs = self.documents.keys().map(|id| (*id, 0f32)).collect();
+                }
+
+                for (_, ranks) in results.iter().filter(|x| x.0 == Occur::MustNot) {
+                    docs.retain(|id, _| !ranks.contains_key(id));
+                }
+
+                for ranks in ranges {
+                    docs.retain(|id, _| ranks.contains_key(id));
+                }
+            }
+            Query::Field(name, query) => {
+                let field = self
+                    .get_field(name)
+                    .ok_or_else(|| QueryError::UnknownField(name.clone()))?;
+
+                docs = self.evaluate(query, options, filter, &[field])?;
+            }
+            Query::Range(start, end) => {
+                for field in fields {
+                    if let FieldValue::Int(_) = field.value {
+                        for id in field.numeric_index.range((*start, *end)) {
+                            docs.insert(id, 0f32);
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(docs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::AnalyzerConfig;
+    use crate::document::Document;
+
+    /// Collection with the fields (`type` or `type:analyzer`) and the documents given as values of every field.
+    fn collection(fields: &[(&str, &str)], docs: &[&[&str]]) -> Collection {
+        let mut collection = Collection::new();
+
+        for (name, kind) in fields {
+            let (kind, analyzer) = kind.split_once(':').unwrap_or((kind, "standard"));
+            collection.push_field_with_analyzer(name, kind.parse().unwrap(), AnalyzerConfig::new(analyzer));
+        }
+
+        for values in docs {
+            let mut doc = Document::new();
+
+            for (field, value) in collection.fields.iter().zip(values.iter()) {
+                let value = match field.value {
+                    FieldValue::Int(_) => FieldValue::Int(value.parse().unwrap()),
+                    FieldValue::Bool(_) => FieldValue::Bool(value.parse().unwrap()),
+                    FieldValue::String(_, _) => FieldValue::String(value.to_string(), None),
+                    FieldValue::Keyword(_) => FieldValue::Keyword(value.to_string()),
+                };
+
+                doc.push(&field.name, value);
+            }
+
+            collection.push(doc, None);
+        }
+
+        collection.commit();
+        collection
+    }
+
+    fn search_with(collection: &Collection, query: &str, options: &SearchOptions) -> Vec<i32> {
+        let mut ids: Vec<i32> = collection.search(query, options).unwrap().hits.iter().map(|x| x.id).collect();
+        ids.sort();
+        ids
+    }
+
+    fn search(collection: &Collection, query: &str) -> Vec<i32> {
+        search_with(collection, query, &SearchOptions::default())
+    }
+
+    #[test]
+    fn test_int_and_bool_terms() {
+        let collection = collection(
+            &[("title", "string"), ("price", "int"), ("sale", "bool")],
+            &[&["dream", "5", "true"], &["dream", "-5", "false"]],
+        );
+
+        assert_eq!(search(&collection, "price:-5"), vec![1]);
+        assert_eq!(search(&collection, "price:5"), vec![0]);
+        assert_eq!(search(&collection, "sale:true"), vec![0]);
+    }
+
+    #[test]
+    fn test_filter() {
+        let collection = collection(
+            &[("title", "string"), ("category", "keyword"), ("in_stock", "bool")],
+            &[
+                &["dream dream", "books", "true"],
+                &["dream", "books", "false"],
+                &["dream", "music", "true"],
+                &["night", "books", "true"],
+            ],
+        );
+        let scores = |filter: Option<&str>| -> Vec<(i32, f32)> {
+            let options = SearchOptions {
+                filter: filter.map(|x| x.into()),
+                ..Default::default()
+            };
+            let result = collection.search("dream", &options).unwrap();
+            let mut hits: Vec<(i32, f32)> = result.hits.iter().map(|x| (x.id, x.score)).collect();
+            hits.sort_by_key(|x| x.0);
+            hits
+        };
+
+        let all = scores(None);
+        assert_eq!(all.iter().map(|x| x.0).collect::<Vec<_>>(), vec![0, 1, 2]);
+        assert_eq!(scores(Some("in_stock:true")), vec![all[0], all[2]]);
+        assert_eq!(scores(Some("in_stock:true AND category:books")), vec![all[0]]);
+        assert_eq!(scores(Some("category:Books")), vec![]);
+        //terms match whole values, `dream dream` isn't `dream`
+        assert_eq!(scores(Some("title:dream")), vec![all[1], all[2]]);
+    }
+
+    #[test]
+    fn test_keywords() {
+        let collection = collection(
+            &[("title", "string"), ("sku", "keyword")],
+            &[&["dream", "AB-12"], &["dream", "ab-12"], &["dream", "AB-123"], &["night", "CD 12"]],
+        );
+
+        //whole values, case-sensitive and unanalyzed
+        assert_eq!(search(&collection, "sku:AB-12"), vec![0]);
+        assert_eq!(search(&collection, "sku:ab-12"), vec![1]);
+        assert_eq!(search(&collection, "sku:\"CD 12\""), vec![3]);
+        assert!(search(&collection, "sku:AB").is_empty());
+        assert!(search(&collection, "sku:12").is_empty());
+
+        assert_eq!(search(&collection, "sku:AB*"), vec![0, 2]);
+        assert_eq!(search(&collection, "sku:A?-12"), vec![0]);
+        assert_eq!(search(&collection, "sku:a?-12"), vec![1]);
+        assert_eq!(search(&collection, "sku:*-12*"), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn test_suggest_below() {
+        let collection = collection(&[("title", "string")], &[&["dream"], &["dreams"]]);
+        let suggestions = |query: &str, suggest_below: Option<usize>| {
+            let options = SearchOptions {
+                suggest_below,
+                ..Default::default()
+            };
+            collection.search(query, &options).unwrap().suggestions
+        };
+
+        assert_eq!(suggestions("dreem", None), vec!["dream"]);
+        assert!(suggestions("dreem", Some(0)).is_empty());
+        assert_eq!(suggestions("dreem", Some(1)), vec!["dream"]);
+        assert!(suggestions("dreem OR dream", None).is_empty());
+        assert_eq!(suggestions("dreem OR dream", Some(5)), vec!["dream OR dream"]);
+    }
+
+    #[test]
+    fn test_pagination() {
+        let collection = collection(&[("title", "string")], &[&["dream"], &["dream"], &["dream"]]);
+        let options = SearchOptions {
+            limit: Some(2),
+            ..Default::default()
+        };
+        let result = collection.search("dream", &options).unwrap();
+        assert_eq!(result.hits.len(), 2);
+
+        let options = SearchOptions {
+            search_after: result.cursor,
+            ..Default::default()
+        };
+        assert_eq!(collection.search("dream", &options).unwrap().hits.len(), 1);
+
+        let options = SearchOptions {
+            offset: usize::MAX,
+            ..Default::default()
+        };
+        assert!(collection.search("dream", &options).unwrap().hits.is_empty());
+
+        for cursor in ["zz", "fdffffffffffffff7f", ""] {
+            let options = SearchOptions {
+                search_after: Some(cursor.into()),
+                ..Default::default()
+            };
+            assert_eq!(collection.search("dream", &options).err(), Some(QueryError::InvalidCursor));
+        }
+    }
+
+    #[test]
+    fn test_colons() {
+        let collection = collection(&[("title", "string")], &[&["Meeting at 10:30"], &["Visit http://example.com"]]);
+
+        assert_eq!(search(&collection, "10:30"), vec![0]);
+        assert_eq!(search(&collection, "title:10:30"), vec![0]);
+        assert_eq!(search(&collection, "http://example.com"), vec![1]);
+        assert_eq!(
+            collection.search("titel:10", &SearchOptions::default()).err(),
+            Some(QueryError::UnknownField("titel".into()))
+        );
+    }
+
+    #[test]
+    fn test_ranges() {
+        let collection = collection(
+            &[("title", "string"), ("price", "int")],
+            &[&["dream big", "10"], &["dream small", "1"], &["honest work", "7"]],
+        );
+        let score = |query| collection.search(query, &SearchOptions::default()).unwrap().hits[0].score;
+
+        assert_eq!(search(&collection, "title:dream price>=5"), vec![0]);
+        assert_eq!(score("title:dream price>=5"), score("title:dream price>=0"));
+        assert_eq!(search(&collection, "+title:dream price<5 -small"), Vec::<i32>::new());
+        assert_eq!(search(&collection, "price>=5"), vec![0, 2]);
+        assert_eq!(search(&collection, "price>=8 OR price<2"), vec![0, 1]);
+        assert_eq!(search(&collection, "I <3 dream"), vec![0, 1]);
+        assert_eq!(search(&collection, "dream -> work"), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn test_prefix() {
+        let collection = collection(&[("title", "string")], &[&["Dream big"], &["Sweet dreams"], &["Drama club"]]);
+
+        assert_eq!(search(&collection, "dr*"), vec![0, 1, 2]);
+        assert_eq!(search(&collection, "dre*"), vec![0, 1]);
+        assert_eq!(search(&collection, "title:dra*"), vec![2]);
+        assert!(search(&collection, "dri*").is_empty());
+    }
+
+    #[test]
+    fn test_prefix_unstemmed() {
+        let collection = collection(&[("title", "string")], &[&["Dreaming of Rust"], &["Dreams come true"], &["Café au lait"]]);
+
+        assert_eq!(search(&collection, "dreaming*"), vec![0, 1]);
+        assert_eq!(search(&collection, "dreami*"), vec![0, 1]);
+        assert_eq!(search(&collection, "dream*"), vec![0, 1]);
+        assert_eq!(search(&collection, "dreamt*"), Vec::<i32>::new());
+        assert_eq!(search(&collection, "Café*"), vec![2]);
+        assert_eq!(search(&collection, "cafe*"), vec![2]);
+    }
+
+    #[test]
+    fn test_patterns() {
+        let collection = collection(&[("title", "string")], &[&["Dreaming of Rust"], &["honest work"], &["Café au lait"]]);
+
+        assert_eq!(search(&collection, "*ing"), vec![0]);
+        assert_eq!(search(&collection, "hon?st"), vec![1]);
+        assert_eq!(search(&collection, "/dream(ing|s)/"), vec![0]);
+        assert_eq!(search(&collection, "c?fe"), vec![2]);
+    }
+
+    #[test]
+    fn test_cjk_terms() {
+        let collection = collection(&[("cjk", "string:cjk")], &[&["東京都に住む"], &["京都の寺"]]);
+
+        assert_eq!(search(&collection, "cjk:東京都"), vec![0]);
+        assert_eq!(search(&collection, "京都"), vec![0, 1]);
+        assert_eq!(search(&collection, "\"京都の寺\""), vec![1]);
+    }
+
+    #[test]
+    fn test_ngram_terms() {
+        let collection = collection(&[("title", "string:ngram")], &[&["smartphone"], &["photo"], &["honey"], &["phone"]]);
+
+        assert_eq!(search(&collection, "phon"), vec![0, 3]);
+        assert_eq!(search(&collection, "title:phon"), vec![0, 3]);
+        assert_eq!(search(&collection, "phot"), vec![1]);
+        assert_eq!(search(&collection, "hone"), vec![0, 2, 3]);
+        assert_eq!(search(&collection, "ph"), vec![0, 1, 3]);
+    }
+
+    #[test]
+    fn test_ngram_order() {
+        //a word with the grams of the query word in another order doesn't contain it
+        let collection = collection(&[("title", "string:ngram")], &[&["honpho"], &["phone"]]);
+
+        assert_eq!(search(&collection, "phon"), vec![1]);
+        assert_eq!(search(&collection, "honp"), vec![0]);
+        assert_eq!(search(&collection, "+phon +honp"), Vec::<i32>::new());
+    }
+
+    #[test]
+    fn test_edge_ngram_terms() {
+        let collection = collection(
+            &[("title", "string:edge_ngram")],
+            &[&["photo"], &["phones"], &["photograph"], &["smartphone"]],
+        );
+
+        assert_eq!(search(&collection, "ph"), vec![0, 1, 2]);
+        assert_eq!(search(&collection, "pho"), vec![0, 1, 2]);
+        assert_eq!(search(&collection, "phon"), vec![1]);
+        assert_eq!(search(&collection, "phone"), vec![1]);
+        assert_eq!(search(&collection, "photog"), vec![2]);
+        assert_eq!(search(&collection, "photographs"), Vec::<i32>::new());
+        assert_eq!(search(&collection, "p"), vec![0, 1, 2]);
+    }
+    #[test]
+    fn test_multi_word_synonyms() {
+        let mut collection = collection(&[("title", "string")], &[&["Trip to NYC"], &["The mp3player review"], &["York minster"]]);
+        collection.synonyms = vec!["nyc, new york".parse().unwrap(), "mp3 player, mp3player".parse().unwrap()];
+
+        //the words of the entry are separate terms of the query
+        assert_eq!(search(&collection, "new york"), vec![0, 2]);
+        assert_eq!(search(&collection, "mp3 player"), vec![1]);
+        assert_eq!(search(&collection, "\"new york\""), vec![0]);
+        assert_eq!(search(&collection, "nyc"), vec![0]);
+        assert_eq!(search(&collection, "+new +york"), Vec::<i32>::new());
     }
 }
@@ -0,0 +1,140 @@
+use std::cmp::Ordering;
+use std::str::FromStr;
+
+use bincode::{Decode, Encode};
+
+use crate::collection::Collection;
+use crate::field::FieldValue;
+use crate::search::Hit;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Order {
+    Asc,
+    Desc,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SortBy {
+    /// Relevance of the document, `_score` in the sort string.
+    Score,
+    Field(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Encode, Decode)]
+pub enum SortValue {
+    Score(f32),
+    Field(Option<FieldValue>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sort {
+    pub by: SortBy,
+    pub order: Order,
+}
+
+impl Sort {
+    pub fn score() -> Self {
+        Sort {
+            by: SortBy::Score,
+            order: Order::Desc,
+        }
+    }
+
+    /// Value the hit is sorted by.
+    pub fn value(&self, collection: &Collection, hit: &Hit) -> SortValue {
+        match &self.by {
+            SortBy::Score => SortValue::Score(hit.score),
+            SortBy::Field(name) => {
+                let value = collection.get(hit.id).and_then(|doc| doc.get(name));
+
+                SortValue::Field(value.map(|x| match x {
+                    FieldValue::String(value, _) => FieldValue::String(value.clone(), None),
+                    _ => x.clone(),
+                }))
+            }
+        }
+    }
+
+    pub fn compare(&self, x: &SortValue, y: &SortValue) -> Ordering {
+        let ordering = match (x, y) {
+            (SortValue::Score(x), SortValue::Score(y)) => x.total_cmp(y),
+            (SortValue::Field(Some(x)), SortValue::Field(Some(y))) => match (x, y) {
+                (FieldValue::Int(x), FieldValue::Int(y)) => x.cmp(y),
+                (FieldValue::Bool(x), FieldValue::Bool(y)) => x.cmp(y),
+                (FieldValue::String(x, _), FieldValue::String(y, _)) => x.cmp(y),
+                (FieldValue::Keyword(x), FieldValue::Keyword(y)) => x.cmp(y),
+                (x, y) => x.as_string().cmp(&y.as_string()),
+            },
+            //documents without the value go last in both orders
+            (SortValue::Field(Some(_)), SortValue::Field(None)) => return Ordering::Less,
+            (SortValue::Field(None), SortValue::Field(Some(_))) => return Ordering::Greater,
+            _ => Ordering::Equal,
+        };
+
+        match self.order {
+            Order::Asc => ordering,
+            Order::Desc => ordering.reverse(),
+        }
+    }
+
+    pub fn values(sorts: &[Sort], collection: &Collection, hit: &Hit) -> Vec<SortValue> {
+        sorts.iter().map(|sort| sort.value(collection, hit)).collect()
+    }
+
+    /// Compares the values by every sort in turn, then the ids, so the order is always the same.
+    pub fn compare_all(sorts: &[Sort], x: (&[SortValue], i32), y: (&[SortValue], i32)) -> Ordering {
+        sorts
+            .iter()
+            .zip(x.0.iter().zip(y.0))
+            .map(|(sort, (x, y))| sort.compare(x, y))
+            .find(|x| x.is_ne())
+            .unwrap_or_else(|| x.1.cmp(&y.1))
+    }
+}
+
+impl FromStr for Sort {
+    type Err = ();
+
+    /// Parses `field`, `field:asc` or `field:desc`, `_score` stands for relevance.
+    /// Fields are sorted in ascending order by default, relevance in descending.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, order) = match s.split_once(':') {
+            Some((name, order)) => (name, Some(order)),
+            None => (s, None),
+        };
+
+        let by = match name {
+            "" => return Err(()),
+            "_score" => SortBy::Score,
+            _ => SortBy::Field(name.to_string()),
+        };
+
+        let order = match order {
+            Some("asc") => Order::Asc,
+            Some("desc") => Order::Desc,
+            Some(_) => return Err(()),
+            None if by == SortBy::Score => Order::Desc,
+            None => Order::Asc,
+        };
+
+        Ok(Sort { by, order })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            "created_at:desc".parse(),
+            Ok(Sort {
+                by: SortBy::Field("created_at".into()),
+                order: Order::Desc,
+            })
+        );
+        assert_eq!("_score".parse(), Ok(Sort::score()));
+        assert_eq!("price:up".parse::<Sort>(), Err(()));
+    }
+}
@@ -0,0 +1,66 @@
+use crate::language::Language;
+
+const ENGLISH: &[&str] = &[
+    "a", "an", "and", "are", "as", "at", "be", "but", "by", "for", "if", "in", "into", "is", "it", "no", "not",
+    "of", "on", "or", "such", "that", "the", "their", "then", "there", "these", "they", "this", "to", "was",
+    "will", "with",
+];
+
+const GERMAN: &[&str] = &[
+    "aber", "als", "am", "an", "auch", "auf", "aus", "bei", "bin", "bis", "das", "dass", "dem", "den", "der",
+    "des", "die", "doch", "du", "ein", "eine", "einem", "einen", "einer", "eines", "er", "es", "für", "hat",
+    "ich", "ihr", "im", "in", "ist", "mit", "nach", "nicht", "noch", "oder", "sich", "sie", "sind", "so",
+    "über", "um", "und", "uns", "von", "vor", "war", "wie", "wir", "zu", "zum", "zur",
+];
+
+const RUSSIAN: &[&str] = &[
+    "а", "без", "бы", "в", "во", "вот", "все", "вы", "да", "для", "до", "его", "ее", "если", "есть", "же",
+    "за", "и", "из", "или", "им", "их", "к", "как", "ко", "ли", "мы", "на", "не", "нет", "ни", "но", "о",
+    "об", "он", "она", "они", "от", "по", "при", "с", "со", "так", "то", "у", "уже", "что", "это", "я",
+];
+
+const FRENCH: &[&str] = &[
+    "au", "aux", "avec", "ce", "ces", "dans", "de", "des", "du", "elle", "en", "et", "il", "ils", "je", "la",
+    "le", "les", "leur", "lui", "ma", "mais", "me", "mes", "ne", "nous", "on", "ou", "par", "pas", "pour",
+    "qu", "que", "qui", "sa", "se", "ses", "son", "sur", "ta", "te", "tes", "toi", "ton", "tu", "un", "une",
+    "vous",
+];
+
+const SPANISH: &[&str] = &[
+    "a", "al", "como", "con", "de", "del", "el", "en", "es", "esta", "este", "la", "las", "le", "les", "lo",
+    "los", "más", "mi", "no", "o", "para", "pero", "por", "que", "se", "si", "sin", "su", "sus", "un", "una",
+    "uno", "y", "ya",
+];
+
+const ITALIAN: &[&str] = &[
+    "a", "al", "alla", "che", "chi", "con", "da", "dal", "dei", "del", "della", "di", "e", "è", "gli", "il",
+    "in", "la", "le", "lo", "ma", "mi", "nel", "nella", "non", "o", "per", "più", "se", "si", "su", "sua",
+    "suo", "un", "una", "uno",
+];
+
+const PORTUGUESE: &[&str] = &[
+    "a", "ao", "aos", "as", "com", "como", "da", "das", "de", "do", "dos", "e", "é", "em", "mais", "mas",
+    "na", "nas", "no", "nos", "não", "o", "os", "ou", "para", "pelo", "por", "que", "se", "sem", "seu",
+    "sua", "um", "uma",
+];
+
+const DUTCH: &[&str] = &[
+    "aan", "al", "als", "bij", "dan", "dat", "de", "die", "dit", "een", "en", "er", "het", "hij", "ik", "in",
+    "is", "je", "maar", "met", "na", "niet", "nog", "of", "om", "ook", "op", "te", "tot", "uit", "van",
+    "voor", "was", "wat", "ze", "zich", "zijn",
+];
+
+/// Built-in list of the most common words of the language, empty if there is none.
+pub fn stop_words(language: Language) -> &'static [&'static str] {
+    match language {
+        Language::English => ENGLISH,
+        Language::German => GERMAN,
+        Language::Russian => RUSSIAN,
+        Language::French => FRENCH,
+        Language::Spanish => SPANISH,
+        Language::Italian => ITALIAN,
+        Language::Portuguese => PORTUGUESE,
+        Language::Dutch => DUTCH,
+        _ => &[],
+    }
+}
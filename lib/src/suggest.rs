@@ -0,0 +1,160 @@
+use std::collections::HashMap;
+
+use crate::analyzer::AnalysisMode;
+use crate::collection::Collection;
+use crate::field::{Field, FieldValue};
+use crate::fuzzy;
+use crate::term::Term;
+
+/// Number of correction candidates kept per word.
+const CANDIDATES: usize = 5;
+
+impl Collection {
+    /// Corrected versions of the query, built from the words of the string fields.
+    /// Words missing from the fields are replaced with the closest known words, the ones with fewer edits
+    /// and then found in more documents come first. Nothing if every word is known.
+    pub fn suggest(&self, query: &str, fields: &[&Field], max: usize) -> Vec<String> {
+        let fields: Vec<&Field> = fields
+            .iter()
+            .filter(|x| matches!(x.value, FieldValue::String(_, _)))
+            .copied()
+            .collect();
+
+        //partial suggestions with their total edits and document frequency, the best ones first
+        let mut suggestions: Vec<(String, usize, usize)> = vec![(String::new(), 0, 0)];
+        let mut is_corrected = false;
+        let mut last = 0;
+
+        for (start, word) in words(query) {
+            let candidates = self.candidates(word, &fields);
+            let gap = &query[last..start];
+            last = start + word.len();
+
+            if candidates.is_empty() {
+                for suggestion in suggestions.iter_mut() {
+                    suggestion.0.push_str(gap);
+                    suggestion.0.push_str(word);
+                }
+
+                continue;
+            }
+
+            is_corrected = true;
+
+            let mut next = Vec::new();
+
+            for (text, edits, frequency) in &suggestions {
+                for (word, word_edits, word_frequency) in &candidates {
+                    next.push((format!("{}{}{}", text, gap, word), edits + word_edits, frequency + word_frequency));
+                }
+            }
+
+            next.sort_by(|x, y| x.1.cmp(&y.1).then_with(|| y.2.cmp(&x.2)));
+            next.truncate(max);
+            suggestions = next;
+        }
+
+        if !is_corrected {
+            return Vec::new();
+        }
+
+        suggestions
+            .into_iter()
+            .map(|x| x.0 + &query[last..])
+            .filter(|x| x != query)
+            .collect()
+    }
+
+    /// Closest words to the unknown word with their edits and document frequency, nothing if the word is known.
+    fn candidates(&self, word: &str, fields: &[&Field]) -> Vec<(String, usize, usize)> {
+        //a word analyzed into several terms isn't corrected
+        let terms = fields
+            .iter()
+            .map(|field| match self.analyze(field, word, AnalysisMode::Query).as_slice() {
+                [token] => Some((*field, token.term.clone())),
+                _ => None,
+            })
+            .collect::<Option<Vec<(&Field, Term)>>>()
+            .unwrap_or_default();
+
+        if terms.is_empty() || terms.iter().any(|(field, term)| field.inverted_index.get(term).is_some()) {
+            return Vec::new();
+        }
+
+        //the dictionary holds stems, so the word itself may be closer to them than its own stem
+        let word = word.to_lowercase();
+        let max = fuzzy::max_typos(&word);
+        let mut candidates: HashMap<&Term, (usize, usize, &Field)> = HashMap::new();
+
+        for (field, term) in &terms {
+            for (candidate, postings) in field.inverted_index.terms() {
+                let edits = [&term.value, &word]
+                    .into_iter()
+                    .filter_map(|x| fuzzy::distance(x, &candidate.value, max))
+                    .min();
+
+                if let Some(edits) = edits {
+                    candidates.entry(candidate).or_insert((edits, 0, field)).1 += postings.len();
+                }
+            }
+        }
+
+        let mut terms: Vec<(&Term, (usize, usize, &Field))> = candidates.into_iter().collect();
+        terms.sort_by(|x, y| x.1 .0.cmp(&y.1 .0).then_with(|| y.1 .1.cmp(&x.1 .1)).then_with(|| x.0.cmp(y.0)));
+
+        let mut candidates: Vec<(String, usize, usize)> = Vec::new();
+
+        for (term, (edits, frequency, field)) in terms {
+            let word = self.original_word(field, term).unwrap_or_else(|| term.value.clone());
+
+            if !candidates.iter().any(|x| x.0 == word) {
+                candidates.push((word, edits, frequency));
+            }
+
+            if candidates.len() == CANDIDATES {
+                break;
+            }
+        }
+
+        candidates
+    }
+}
+
+/// Plain words of the query with their byte offsets, operators and field, pattern or range clauses are left out.
+fn words(query: &str) -> impl Iterator<Item = (usize, &str)> {
+    query
+        .split_whitespace()
+        .filter(|x| !x.trim_end_matches('?').contains([':', '*', '?', '/', '<', '>', '~']))
+        .map(move |x| {
+            let word = x.trim_matches(|x: char| !x.is_alphanumeric());
+            (word.as_ptr() as usize - query.as_ptr() as usize, word)
+        })
+        .filter(|(_, x)| !x.is_empty() && x.chars().all(char::is_alphanumeric))
+        .filter(|(_, x)| !["AND", "OR", "NOT"].contains(x))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::document::Document;
+
+    #[test]
+    fn test_suggest() {
+        let mut collection = Collection::new();
+        collection.push_field("title", FieldValue::String(String::new(), None));
+
+        for title in ["Sweet dream", "Big dream", "Cream cake"] {
+            let mut doc = Document::new();
+            doc.push("title", FieldValue::String(title.into(), None));
+            collection.push(doc, None);
+        }
+
+        collection.commit();
+        let fields: Vec<&Field> = collection.fields.iter().collect();
+
+        assert_eq!(collection.suggest("sweet dreem", &fields, 3), vec!["sweet dream"]);
+        assert_eq!(collection.suggest("title:dreem dream", &fields, 3), Vec::<String>::new());
+        assert_eq!(collection.suggest("\"big drem\"", &fields, 1), vec!["\"big dream\""]);
+        assert!(collection.suggest("sweet dream", &fields, 3).is_empty());
+    }
+}
@@ -0,0 +1,143 @@
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use bincode::{Decode, Encode};
+
+use crate::analyzer::{AnalysisMode, Analyzer};
+use crate::collection::Collection;
+use crate::field::Field;
+use crate::term::Term;
+
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub enum Synonym {
+    /// Every entry of the group stands for the others (`tv, television`).
+    Group(Vec<String>),
+    /// The entry stands for the replacements, but not the other way around (`ipod -> mp3 player`).
+    OneWay(String, Vec<String>),
+}
+
+impl Synonym {
+    /// Entries the text stands for, compared by their terms from the analyzer.
+    pub fn expand(&self, text: &str, analyzer: &dyn Analyzer) -> Vec<&str> {
+        let terms = |x: &str| -> Vec<Term> { analyzer.analyze(x).into_iter().map(|x| x.term).collect() };
+        let text = terms(text);
+        let is_match = |x: &String| !text.is_empty() && terms(x) == text;
+
+        match self {
+            Synonym::Group(entries) if entries.iter().any(is_match) => entries
+                .iter()
+                .filter(|x| !is_match(x))
+                .map(|x| x.as_str())
+                .collect(),
+            Synonym::OneWay(entry, replacements) if is_match(entry) => {
+                replacements.iter().map(|x| x.as_str()).collect()
+            }
+            _ => Vec::new(),
+        }
+    }
+}
+
+impl FromStr for Synonym {
+    type Err = ();
+
+    /// Parses `tv, television` groups and `ipod -> mp3 player, mp3` one way synonyms.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let split = |x: &str| -> Vec<String> {
+            x.split(',')
+                .map(|x| x.trim().to_string())
+                .filter(|x| !x.is_empty())
+                .collect()
+        };
+
+        match s.split_once("->") {
+            Some((entry, replacements)) => {
+                let entry = entry.trim();
+                let replacements = split(replacements);
+
+                if entry.is_empty() || entry.contains(',') || replacements.is_empty() {
+                    return Err(());
+                }
+
+                Ok(Synonym::OneWay(entry.to_string(), replacements))
+            }
+            None => {
+                let entries = split(s);
+
+                if entries.len() < 2 {
+                    return Err(());
+                }
+
+                Ok(Synonym::Group(entries))
+            }
+        }
+    }
+}
+
+impl Display for Synonym {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Synonym::Group(entries) => write!(f, "{}", entries.join(", ")),
+            Synonym::OneWay(entry, replacements) => write!(f, "{} -> {}", entry, replacements.join(", ")),
+        }
+    }
+}
+
+impl Collection {
+    /// Entries of the collection synonyms the text stands for, compared as the field analyzes them.
+    pub fn synonyms_of(&self, field: &Field, text: &str) -> Vec<&str> {
+        let analyzer = self.analyzer(field, AnalysisMode::Query);
+        let mut entries: Vec<&str> = Vec::new();
+
+        for entry in self.synonyms.iter().flat_map(|x| x.expand(text, analyzer.as_ref())) {
+            if !entries.contains(&entry) {
+                entries.push(entry);
+            }
+        }
+
+        entries
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::AnalyzerConfig;
+    use crate::field::FieldValue;
+    use crate::language::Language;
+
+    #[test]
+    fn test_expand() {
+        let group: Synonym = "tv, television".parse().unwrap();
+        let one_way: Synonym = "ipod -> mp3 player".parse().unwrap();
+        let analyzer = AnalyzerConfig::default().build(AnalysisMode::Query).unwrap();
+        let analyzer = analyzer.as_ref();
+
+        assert_eq!(group.expand("TV", analyzer), vec!["television"]);
+        assert_eq!(group.expand("televisions", analyzer), vec!["tv"]);
+        assert_eq!(one_way.expand("iPod", analyzer), vec!["mp3 player"]);
+        assert!(one_way.expand("mp3 player", analyzer).is_empty());
+        assert_eq!(one_way.to_string(), "ipod -> mp3 player");
+        assert!("tv".parse::<Synonym>().is_err());
+    }
+
+    #[test]
+    fn test_synonyms_of() {
+        let mut collection = Collection::new();
+        let config = AnalyzerConfig {
+            language: Some(Language::German),
+            ..Default::default()
+        };
+        collection.push_field_with_analyzer("title", FieldValue::String(String::new(), None), config);
+        collection.push_field_with_analyzer("sku", FieldValue::Keyword(String::new()), AnalyzerConfig::default());
+        collection.synonyms = vec!["haus, heim".parse().unwrap(), "AB-1, ab1".parse().unwrap()];
+
+        let title = &collection.fields[0];
+        let sku = &collection.fields[1];
+
+        //stemmed with the field language, `häuser` is `haus` only in german
+        assert_eq!(collection.synonyms_of(title, "Häuser"), vec!["heim"]);
+        //keywords are compared whole and case-sensitive
+        assert_eq!(collection.synonyms_of(sku, "AB-1"), vec!["ab1"]);
+        assert!(collection.synonyms_of(sku, "ab-1").is_empty());
+    }
+}
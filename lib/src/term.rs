@@ -1,6 +1,6 @@
 use bincode::{Decode, Encode};
 
-#[derive(Debug, Clone, Eq, PartialEq, Hash, Encode, Decode)]
+#[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Encode, Decode)]
 pub struct Term {
     pub value: String,
 }
@@ -0,0 +1,23 @@
+use bincode::{Decode, Encode};
+
+use crate::term::Term;
+
+#[derive(Debug, Clone, PartialEq, Encode, Decode)]
+pub struct Token {
+    pub term: Term,
+    pub position: usize,
+    /// Byte offsets of the token in the original text.
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Token {
+    pub fn new(term: Term, position: usize, start: usize, end: usize) -> Self {
+        Token {
+            term,
+            position,
+            start,
+            end,
+        }
+    }
+}
@@ -0,0 +1,178 @@
+use std::collections::HashSet;
+
+use rust_stemmers::{Algorithm, Stemmer};
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
+
+use crate::token::Token;
+
+/// Stage of an analyzer after the tokenizer, changes, removes or adds tokens.
+pub trait TokenFilter: Send + Sync {
+    fn filter(&self, tokens: Vec<Token>) -> Vec<Token>;
+}
+
+/// Unicode compatibility normalization (NFKC), so ligatures, full width and other variants of a letter become the same.
+pub struct NormalizationFilter;
+
+impl TokenFilter for NormalizationFilter {
+    fn filter(&self, mut tokens: Vec<Token>) -> Vec<Token> {
+        for token in tokens.iter_mut().filter(|x| !x.term.value.is_ascii()) {
+            token.term.value = token.term.value.nfkc().collect();
+        }
+
+        tokens
+    }
+}
+
+pub struct LowercaseFilter;
+
+impl TokenFilter for LowercaseFilter {
+    fn filter(&self, mut tokens: Vec<Token>) -> Vec<Token> {
+        for token in tokens.iter_mut() {
+            token.term.value = token.term.value.to_lowercase();
+        }
+
+        tokens
+    }
+}
+
+/// Removes the words of the list, the positions of the remaining tokens stay the same.
+/// Has to come before stemming, as the list has whole lowercase words.
+pub struct StopWordFilter {
+    words: HashSet<String>,
+}
+
+impl StopWordFilter {
+    pub fn new(words: HashSet<String>) -> Self {
+        StopWordFilter { words }
+    }
+}
+
+impl TokenFilter for StopWordFilter {
+    fn filter(&self, mut tokens: Vec<Token>) -> Vec<Token> {
+        tokens.retain(|x| !self.words.contains(&x.term.value));
+        tokens
+    }
+}
+
+/// Removes diacritics and replaces letters without them by their closest ASCII spelling (`café` -> `cafe`, `ß` -> `ss`).
+/// Expects lowercase words.
+pub struct AsciiFoldingFilter;
+
+impl AsciiFoldingFilter {
+    fn fold(letter: char) -> Option<&'static str> {
+        match letter {
+            'ß' => Some("ss"),
+            'æ' => Some("ae"),
+            'œ' => Some("oe"),
+            'ø' => Some("o"),
+            'đ' | 'ð' => Some("d"),
+            'ł' => Some("l"),
+            'þ' => Some("th"),
+            'ı' => Some("i"),
+            _ => None,
+        }
+    }
+}
+
+impl TokenFilter for AsciiFoldingFilter {
+    fn filter(&self, mut tokens: Vec<Token>) -> Vec<Token> {
+        for token in tokens.iter_mut().filter(|x| !x.term.value.is_ascii()) {
+            let mut value = String::new();
+
+            for letter in token.term.value.nfd().filter(|x| !is_combining_mark(*x)) {
+                match Self::fold(letter) {
+                    Some(folded) => value.push_str(folded),
+                    None => value.push(letter),
+                }
+            }
+
+            token.term.value = value;
+        }
+
+        tokens
+    }
+}
+
+/// Adds grams (parts of `min` to `max` characters) of every word after the word, with its position and offsets.
+/// Queries keep their words, a word of `min` to `max` characters is one of the grams and longer or shorter ones
+/// are looked up among the indexed words.
+pub struct NGramFilter {
+    pub min: usize,
+    pub max: usize,
+    /// Only take grams from the beginning of the word.
+    pub edge: bool,
+    /// Keep the words without adding grams.
+    pub query: bool,
+}
+
+impl NGramFilter {
+    fn grams(&self, word: &str) -> Vec<String> {
+        let letters: Vec<char> = word.chars().collect();
+        let (min, max) = (self.min.max(1), self.max);
+        let starts = match self.edge {
+            true => 0..1,
+            false => 0..letters.len(),
+        };
+        let mut grams = Vec::new();
+
+        for start in starts {
+            for size in min..=max {
+                //the whole word is already there
+                if start + size > letters.len() || size == letters.len() {
+                    break;
+                }
+
+                grams.push(letters[start..start + size].iter().collect());
+            }
+        }
+
+        grams
+    }
+}
+
+impl TokenFilter for NGramFilter {
+    fn filter(&self, tokens: Vec<Token>) -> Vec<Token> {
+        let mut result = Vec::new();
+
+        //query words are looked up as they are, the grams of the index are their parts
+        if self.query {
+            return tokens;
+        }
+
+        for token in tokens {
+            let grams = self.grams(&token.term.value);
+            let (position, start, end) = (token.position, token.start, token.end);
+            result.push(token);
+
+            for gram in grams {
+                result.push(Token::new(gram.into(), position, start, end));
+            }
+        }
+
+        result
+    }
+}
+
+/// Reduces words to their stems with the Snowball stemmer of the language.
+pub struct StemmerFilter {
+    stemmer: Stemmer,
+}
+
+impl StemmerFilter {
+    pub fn new(algorithm: Algorithm) -> Self {
+        StemmerFilter {
+            stemmer: Stemmer::create(algorithm),
+        }
+    }
+}
+
+impl TokenFilter for StemmerFilter {
+    fn filter(&self, mut tokens: Vec<Token>) -> Vec<Token> {
+        for token in tokens.iter_mut() {
+            token.term.value = self.stemmer.stem(&token.term.value).to_string();
+        }
+
+        tokens
+    }
+}
@@ -1,25 +1,114 @@
 use std::collections::HashSet;
 
 use lazy_static::lazy_static;
-use rust_stemmers::{Algorithm, Stemmer};
+use unicode_segmentation::UnicodeSegmentation;
 
+use crate::analyzer::{standard, Analyzer};
 use crate::term::Term;
+use crate::token::Token;
 
 lazy_static! {
-    static ref STEMMER: Stemmer = Stemmer::create(Algorithm::English);
     static ref SEPARATORS: HashSet<char> = vec![' ', '.', ',', '!', ':', '?'].into_iter().collect();
 }
 
+/// First stage of an analyzer, splits the text into tokens.
+pub trait Tokenizer: Send + Sync {
+    fn tokenize(&self, text: &str) -> Vec<Token>;
+}
+
+/// Splits the text into words on Unicode word boundaries (UAX #29), keeping the words as they are.
+/// Words are also split on the punctuation UAX #29 keeps inside them (`end.Start`, `3.14`),
+/// so plain ASCII text is split as before.
+pub struct WordTokenizer;
+
+impl Tokenizer for WordTokenizer {
+    fn tokenize(&self, text: &str) -> Vec<Token> {
+        text.unicode_words()
+            .flat_map(|x| x.split(|x: char| SEPARATORS.contains(&x)))
+            .filter(|x| !x.is_empty())
+            .enumerate()
+            .map(|(position, x)| {
+                let start = x.as_ptr() as usize - text.as_ptr() as usize;
+                Token::new(x.into(), position, start, start + x.len())
+            })
+            .collect()
+    }
+}
+
+/// Splits the text into words like `WordTokenizer`, but runs of Chinese, Japanese and Korean characters,
+/// which aren't separated by spaces, become overlapping bigrams (`東京都` -> `東京`, `京都`).
+/// A single CJK character stays a token of its own.
+pub struct CjkTokenizer;
+
+impl CjkTokenizer {
+    fn is_cjk(letter: char) -> bool {
+        matches!(letter as u32,
+            0x1100..=0x11FF //Hangul Jamo
+            | 0x3040..=0x30FF //Hiragana and Katakana
+            | 0x3130..=0x318F //Hangul compatibility Jamo
+            | 0x31F0..=0x31FF //Katakana phonetic extensions
+            | 0x3400..=0x4DBF //CJK unified ideographs extension A
+            | 0x4E00..=0x9FFF //CJK unified ideographs
+            | 0xAC00..=0xD7AF //Hangul syllables
+            | 0xF900..=0xFAFF //CJK compatibility ideographs
+            | 0xFF65..=0xFF9F //Halfwidth Katakana
+            | 0x20000..=0x2FA1F //CJK unified ideographs extensions
+        )
+    }
+
+    /// Bigrams of the run with their byte offsets in the text.
+    fn bigrams(text: &str, start: usize, end: usize) -> Vec<(usize, usize)> {
+        let mut letters: Vec<usize> = text[start..end].char_indices().map(|(i, _)| start + i).collect();
+        letters.push(end);
+
+        if letters.len() <= 3 {
+            return vec![(start, end)];
+        }
+
+        letters.windows(3).map(|x| (x[0], x[2])).collect()
+    }
+}
+
+impl Tokenizer for CjkTokenizer {
+    fn tokenize(&self, text: &str) -> Vec<Token> {
+        let words = WordTokenizer.tokenize(text);
+        //spans of the words and of the runs of adjacent CJK words, which are marked
+        let mut spans: Vec<(usize, usize, bool)> = Vec::new();
+
+        for word in words {
+            let is_cjk = text[word.start..word.end].chars().all(Self::is_cjk);
+
+            match spans.last_mut() {
+                Some(last) if is_cjk && last.2 && last.1 == word.start => last.1 = word.end,
+                _ => spans.push((word.start, word.end, is_cjk)),
+            }
+        }
+
+        spans
+            .into_iter()
+            .flat_map(|(start, end, is_cjk)| match is_cjk {
+                true => Self::bigrams(text, start, end),
+                false => vec![(start, end)],
+            })
+            .enumerate()
+            .map(|(position, (start, end))| Token::new(text[start..end].into(), position, start, end))
+            .collect()
+    }
+}
+
+/// Terms of the text produced by the standard analyzer.
 pub fn tokenize(text: &str) -> Vec<Term> {
-    text.split(|x: char| (&SEPARATORS).contains(&x))
-        .map(|x| x.trim())
-        .filter(|x| !x.is_empty())
-        .map(|x| Term {
-            value: STEMMER.stem(x.to_lowercase().as_str()).to_string(),
-        })
+    tokenize_with_positions(text)
+        .into_iter()
+        .map(|x| x.term)
         .collect()
 }
 
+/// Tokenizes the text with the standard analyzer, keeping position and byte offsets of every token.
+pub fn tokenize_with_positions(text: &str) -> Vec<Token> {
+    standard().analyze(text)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -31,4 +120,34 @@ mod tests {
             vec!["hello".into(), "world".into()]
         );
     }
+
+    #[test]
+    fn test_tokenize_with_positions() {
+        let tokens = tokenize_with_positions("New York, new!");
+        let positions: Vec<usize> = tokens.iter().map(|x| x.position).collect();
+
+        assert_eq!(positions, vec![0, 1, 2]);
+        assert_eq!(tokens[2].term, "new".into());
+        assert_eq!((tokens[1].start, tokens[1].end), (4, 8));
+    }
+
+    #[test]
+    fn test_tokenize_cjk() {
+        let terms: Vec<String> = CjkTokenizer
+            .tokenize("東京都に住む Rust 개발자")
+            .into_iter()
+            .map(|x| x.term.value)
+            .collect();
+
+        assert_eq!(terms, vec!["東京", "京都", "都に", "に住", "住む", "Rust", "개발", "발자"]);
+    }
+
+    #[test]
+    fn test_tokenize_unicode() {
+        assert_eq!(
+            tokenize("hello-world\t(test) foo/bar \"Café\""),
+            vec!["hello".into(), "world".into(), "test".into(), "foo".into(), "bar".into(), "cafe".into()]
+        );
+        assert_eq!(tokenize("ﬁle ＲＵＳＴ"), tokenize("file rust"));
+    }
 }
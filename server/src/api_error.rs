@@ -4,16 +4,33 @@ use salvo::hyper::StatusCode;
 use salvo::writer::Json;
 use serde_json::json;
 
+use sightnet_core::query::QueryError;
+
+#[derive(Debug, PartialEq)]
 pub struct ApiError {
     code: i32,
-    msg: &'static str,
+    msg: String,
 }
 
 impl ApiError {
-    pub fn new(code: i32, msg: &'static str) -> Self {
+    pub fn new(code: i32, msg: &str) -> Self {
         Self {
             code,
-            msg,
+            msg: msg.to_string(),
+        }
+    }
+}
+
+impl From<QueryError> for ApiError {
+    fn from(error: QueryError) -> Self {
+        match error {
+            QueryError::UnbalancedParenthesis => ApiError::new(21, "Query has unbalanced parentheses."),
+            QueryError::MissingOperand => ApiError::new(22, "Query operator is missing an operand."),
+            QueryError::UnknownField(name) => ApiError::new(23, &format!("There is no field named `{}`.", name)),
+            QueryError::InvalidNumber(_) => ApiError::new(24, "Range value is not an integer."),
+            QueryError::InvalidAggregation(_) => ApiError::new(26, "Aggregations need an int field and a positive interval."),
+            QueryError::InvalidCursor => ApiError::new(28, "You have provided invalid search_after cursor."),
+            QueryError::InvalidPattern(_) => ApiError::new(29, "Query has an invalid regular expression."),
         }
     }
 }
@@ -8,7 +8,7 @@ use salvo::prelude::*;
 use routes::collection;
 use sightnet_core::file::File;
 
-use crate::routes::document;
+use crate::routes::{document, stop_words, synonym};
 use crate::routes::state::STATE;
 
 mod routes;
@@ -32,7 +32,14 @@ fn load_collections() {
         let file_path = file.path();
         let file_name = file_path.to_str().unwrap();
         let collection_id = Path::new(file_name).file_stem().unwrap().to_str().unwrap();
-        let collection = File::load(file_name).expect("Failed loading collections");
+        //a file which can't be read is left as it is, so it doesn't get overwritten
+        let collection = match File::load(file_name) {
+            Ok(collection) => collection,
+            Err(error) => {
+                eprintln!("Skipped {} ({})", collection_id, error);
+                continue;
+            }
+        };
 
         println!("Loaded {} ({})", collection_id, collection.len());
         STATE.lock().unwrap().collections.insert(collection_id.to_string(), Arc::new(Mutex::new(collection)));
@@ -60,6 +67,26 @@ async fn main() {
                         Router::with_path("search")
                             .get(collection::search)
                     )
+                    .push(
+                        Router::with_path("autocomplete")
+                            .get(collection::autocomplete)
+                    )
+                    .push(
+                        Router::with_path("analyze")
+                            .post(collection::analyze)
+                    )
+                    .push(
+                        Router::with_path("synonyms")
+                            .get(synonym::list)
+                            .put(synonym::update)
+                            .delete(synonym::clear)
+                    )
+                    .push(
+                        Router::with_path("stop_words")
+                            .get(stop_words::list)
+                            .put(stop_words::update)
+                            .delete(stop_words::clear)
+                    )
                     .push(
                         Router::with_path("commit")
                             .get(collection::commit)
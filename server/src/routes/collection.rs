@@ -1,11 +1,19 @@
+use std::collections::HashMap;
 use std::str::FromStr;
 use std::sync::{Arc, Mutex};
 
 use salvo::prelude::*;
-use serde_json::{json, Value};
+use serde_json::{json, Map, Value};
 
+use sightnet_core::analyzer::{self, AnalysisMode, AnalyzerConfig};
 use sightnet_core::collection::Collection;
 use sightnet_core::field::{FieldValue};
+use sightnet_core::aggregation::{Aggregation, AggregationResult};
+use sightnet_core::highlight::HighlightOptions;
+use sightnet_core::language::Language;
+use sightnet_core::query::QueryError;
+use sightnet_core::search::SearchOptions;
+use sightnet_core::sort::Sort;
 
 use crate::api_error::ApiError;
 use crate::api_result::ApiResult;
@@ -44,8 +52,143 @@ pub async fn get_is_strict(req: &mut Request) -> Result<bool, ApiError> {
     Ok(is_strict.unwrap())
 }
 
-pub async fn get_max(req: &mut Request) -> Option<usize> {
-    req.param::<usize>("max")
+pub async fn get_list(req: &mut Request, name: &str) -> Option<Vec<String>> {
+    let list = req.query::<String>(name)?;
+
+    Some(list.split(',').map(|x| x.trim().to_string()).collect())
+}
+
+pub async fn get_filter(req: &mut Request) -> Option<String> {
+    req.query::<String>("filter")
+}
+
+/// Parses `aggs=stats:price,histogram:price:10`, results are named after their definitions.
+pub async fn get_aggregations(req: &mut Request) -> Result<HashMap<String, Aggregation>, ApiError> {
+    let mut aggregations = HashMap::new();
+
+    for definition in get_list(req, "aggs").await.unwrap_or_default() {
+        let parts: Vec<&str> = definition.split(':').collect();
+        let aggregation = match parts[..] {
+            ["stats", field] => Aggregation::Stats(field.into()),
+            ["histogram", field, interval] => match interval.parse::<i64>() {
+                Ok(interval) => Aggregation::Histogram(field.into(), interval),
+                Err(_) => return Err(ApiError::new(25, "You have provided invalid aggregation.")),
+            },
+            _ => return Err(ApiError::new(25, "You have provided invalid aggregation.")),
+        };
+
+        aggregations.insert(definition, aggregation);
+    }
+
+    Ok(aggregations)
+}
+
+pub async fn get_sort(req: &mut Request) -> Result<Vec<Sort>, ApiError> {
+    let sort = get_list(req, "sort").await.unwrap_or_default();
+
+    sort.iter()
+        .map(|x| Sort::from_str(x).map_err(|_| ApiError::new(27, "You have provided invalid sort.")))
+        .collect()
+}
+
+pub async fn get_highlight(req: &mut Request) -> Option<HighlightOptions> {
+    if !req.query::<bool>("highlight").unwrap_or(false) {
+        return None;
+    }
+
+    let default = HighlightOptions::default();
+
+    Some(HighlightOptions {
+        fields: get_list(req, "highlight_fields").await,
+        pre_tag: req.query::<String>("pre_tag").unwrap_or(default.pre_tag),
+        post_tag: req.query::<String>("post_tag").unwrap_or(default.post_tag),
+        snippet_size: req.query::<usize>("snippet_size").unwrap_or(default.snippet_size),
+    })
+}
+
+/// Parses a field definition, either a type (`"string"`) or an object with the type and the analysis settings
+/// (`{"type": "string", "analyzer": "standard", "language": "german", "stop_words": true}`).
+pub fn get_field_definition(definition: &Value) -> Result<(FieldValue, AnalyzerConfig), ApiError> {
+    let empty = Map::new();
+    let (kind, options) = match definition {
+        Value::Object(options) => (options.get("type"), options),
+        definition => (Some(definition), &empty),
+    };
+
+    let value = kind
+        .and_then(|x| x.as_str())
+        .and_then(|x| FieldValue::from_str(x).ok())
+        .ok_or_else(|| ApiError::new(32, "You have provided invalid field type."))?;
+
+    let mut analyzer = match options.get("analyzer") {
+        Some(name) => match name.as_str() {
+            Some(name) if analyzer::is_registered(name) => AnalyzerConfig::new(name),
+            _ => return Err(ApiError::new(33, "There is no analyzer with such name.")),
+        },
+        None => AnalyzerConfig::default(),
+    };
+
+    //`"none"` turns stemming off
+    if let Some(language) = options.get("language") {
+        analyzer.language = match language.as_str() {
+            Some("none") => None,
+            Some(language) => Some(get_language(language)?),
+            None => return Err(ApiError::new(34, "You have provided unsupported language.")),
+        };
+    }
+
+    //`true` takes the stop words of the field language
+    if let Some(stop_words) = options.get("stop_words") {
+        analyzer.stop_words = match stop_words {
+            Value::Bool(true) => Some(analyzer.language.unwrap_or(Language::English)),
+            Value::Bool(false) => None,
+            Value::String(language) => Some(get_language(language)?),
+            _ => return Err(ApiError::new(34, "You have provided unsupported language.")),
+        };
+    }
+
+    if let Some(words) = options.get("custom_stop_words") {
+        analyzer.custom_stop_words = get_strings(words)
+            .ok_or_else(|| ApiError::new(35, "You have provided invalid stop words."))?;
+    }
+
+    analyzer.keep_stop_words_in_phrases = options
+        .get("keep_stop_words_in_phrases")
+        .and_then(|x| x.as_bool())
+        .unwrap_or(false);
+
+    analyzer.html = options.get("html").and_then(|x| x.as_bool()).unwrap_or(false);
+
+    for (name, size) in [("min_gram", &mut analyzer.min_gram), ("max_gram", &mut analyzer.max_gram)] {
+        if let Some(value) = options.get(name) {
+            *size = value
+                .as_u64()
+                .ok_or_else(|| ApiError::new(36, "You have provided invalid gram sizes."))? as usize;
+        }
+    }
+
+    if analyzer.min_gram == 0 || analyzer.min_gram > analyzer.max_gram {
+        return Err(ApiError::new(36, "You have provided invalid gram sizes."));
+    }
+
+    Ok((value, analyzer))
+}
+
+pub fn get_language(language: &str) -> Result<Language, ApiError> {
+    Language::from_str(language).map_err(|_| ApiError::new(34, "You have provided unsupported language."))
+}
+
+/// Strings of a json array, `None` if it isn't an array of strings.
+pub fn get_strings(value: &Value) -> Option<Vec<String>> {
+    value
+        .as_array()?
+        .iter()
+        .map(|x| x.as_str().map(|x| x.to_string()))
+        .collect()
+}
+
+pub async fn get_limit(req: &mut Request) -> Option<usize> {
+    req.query::<usize>("limit").or_else(|| req.query::<usize>("max"))
 }
 
 #[handler]
@@ -83,8 +226,8 @@ pub async fn create(req: &mut Request) -> Result<ApiResult, ApiError> {
     let mut collection = Collection::new();
 
     for field in fields {
-        let value = FieldValue::from_str(field.1.as_str().unwrap()).unwrap();
-        collection.push_field(field.0, value);
+        let (value, analyzer) = get_field_definition(field.1)?;
+        collection.push_field_with_analyzer(field.0, value, analyzer);
     }
 
     let home = dirs::home_dir().unwrap();
@@ -117,21 +260,198 @@ pub async fn commit(req: &mut Request) -> Result<ApiResult, ApiError> {
 pub async fn search(req: &mut Request) -> Result<ApiResult, ApiError> {
     let id = get_collection_id(req).await?;
     let query = get_query(req).await?;
-    let is_strict = get_is_strict(req).await?;
-    let max = get_max(req).await;
+    let options = SearchOptions {
+        strict: get_is_strict(req).await?,
+        fields: get_list(req, "fields").await,
+        offset: req.query::<usize>("offset").unwrap_or_default(),
+        limit: get_limit(req).await,
+        search_after: req.query::<String>("search_after"),
+        filter: get_filter(req).await,
+        facets: get_list(req, "facets").await.unwrap_or_default(),
+        facet_size: req.query::<usize>("facet_size"),
+        aggregations: get_aggregations(req).await?,
+        sort: get_sort(req).await?,
+        highlight: get_highlight(req).await,
+        max_expansions: req.query::<usize>("max_expansions"),
+        typo_tolerance: req.query::<bool>("typo_tolerance").unwrap_or(false),
+        suggest_below: req.query::<usize>("suggest_below"),
+    };
     let collection = get_collection(id.clone()).await?;
     let collection = collection.lock().unwrap();
 
-    let results = collection.search(query.as_str(), is_strict, None, max);
-    let mut json_results = Vec::new();
+    let result = collection.search(query.as_str(), &options)?;
+    let mut json_hits = Vec::new();
+    let mut json_facets = json!({});
+    let mut json_aggregations = json!({});
 
-    for result in results {
-        let document = collection.get(result.0);
+    for hit in result.hits {
+        let document = collection.get(hit.id);
         let mut json = generate_fields_json(&document.unwrap().fields);
-        json["id"] = json!(result.0);
-        json["rank"] = json!(result.1);
-        json_results.push(json);
+        json["id"] = json!(hit.id);
+        json["rank"] = json!(hit.score);
+
+        if options.highlight.is_some() {
+            json["highlights"] = json!({});
+
+            for (name, highlight) in hit.highlights {
+                json["highlights"][name] = json!({
+                    "fragment": highlight.fragment,
+                    "snippet": highlight.snippet,
+                });
+            }
+        }
+
+        json_hits.push(json);
+    }
+
+    for (name, facets) in result.facets {
+        json_facets[name] = facets
+            .iter()
+            .map(|x| json!({ "value": x.value, "count": x.count }))
+            .collect();
+    }
+
+    for (name, aggregation) in result.aggregations {
+        json_aggregations[name] = match aggregation {
+            AggregationResult::Stats(stats) => json!({
+                "count": stats.count,
+                "min": stats.min,
+                "max": stats.max,
+                "avg": stats.avg,
+                //sums beyond i64 lose precision instead of failing the serialization
+                "sum": i64::try_from(stats.sum).map(Value::from).unwrap_or(Value::from(stats.sum as f64)),
+            }),
+            AggregationResult::Histogram(buckets) => buckets
+                .iter()
+                .map(|x| json!({ "key": x.key, "count": x.count }))
+                .collect(),
+        };
+    }
+
+    Ok(ApiResult::new(Some(json!({
+        "hits": json_hits,
+        "total": result.total,
+        "cursor": result.cursor,
+        "facets": json_facets,
+        "aggregations": json_aggregations,
+        "suggestions": result.suggestions,
+    }))))
+}
+
+#[handler]
+pub async fn autocomplete(req: &mut Request) -> Result<ApiResult, ApiError> {
+    let id = get_collection_id(req).await?;
+    let query = get_query(req).await?;
+    let fields = get_list(req, "fields").await;
+    let limit = get_limit(req).await;
+    let collection = get_collection(id.clone()).await?;
+    let collection = collection.lock().unwrap();
+
+    let completions = collection.autocomplete(query.as_str(), fields, limit)?;
+    Ok(ApiResult::new(Some(json!(completions))))
+}
+
+/// Tokens the analyzer of the field produces from the text, for `{"field": "title", "text": "Dreaming", "mode": "query"}`.
+/// Mode is `index`, `query` (default) or `phrase`, as stop words may be kept for indexing and phrases.
+#[handler]
+pub async fn analyze(req: &mut Request) -> Result<ApiResult, ApiError> {
+    let id = get_collection_id(req).await?;
+    let collection = get_collection(id).await?;
+    let json = get_json_body(req).await?;
+    let tokens = analyze_json(&collection.lock().unwrap(), &json)?;
+
+    Ok(ApiResult::new(Some(json!({ "tokens": tokens }))))
+}
+
+fn analyze_json(collection: &Collection, json: &Value) -> Result<Vec<Value>, ApiError> {
+    let name = json["field"]
+        .as_str()
+        .ok_or_else(|| ApiError::new(37, "You haven't provided field."))?;
+    let text = json["text"]
+        .as_str()
+        .ok_or_else(|| ApiError::new(38, "You haven't provided text."))?;
+    let mode = match json.get("mode").map(|x| x.as_str()) {
+        None | Some(Some("query")) => AnalysisMode::Query,
+        Some(Some("index")) => AnalysisMode::Index,
+        Some(Some("phrase")) => AnalysisMode::Phrase,
+        _ => return Err(ApiError::new(39, "You have provided invalid analysis mode.")),
+    };
+
+    let field = collection
+        .get_field(&name.to_string())
+        .ok_or_else(|| QueryError::UnknownField(name.into()))?;
+
+    let tokens = collection
+        .analyze(field, text, mode)
+        .into_iter()
+        .map(|x| {
+            json!({
+                "term": x.term.value,
+                "word": text[x.start..x.end],
+                "position": x.position,
+                "start": x.start,
+                "end": x.end,
+            })
+        })
+        .collect();
+
+    Ok(tokens)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn collection() -> Collection {
+        let mut collection = Collection::new();
+        let config = AnalyzerConfig {
+            stop_words: Some(Language::English),
+            keep_stop_words_in_phrases: true,
+            ..Default::default()
+        };
+        collection.push_field_with_analyzer("title", FieldValue::String(String::new(), None), config);
+        collection
+    }
+
+    fn terms(json: Value) -> Vec<String> {
+        analyze_json(&collection(), &json)
+            .unwrap()
+            .iter()
+            .map(|x| x["term"].as_str().unwrap().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_analyze() {
+        let tokens = analyze_json(&collection(), &json!({ "field": "title", "text": "Dreaming" })).unwrap();
+        assert_eq!(
+            tokens,
+            vec![json!({ "term": "dream", "word": "Dreaming", "position": 0, "start": 0, "end": 8 })]
+        );
+
+        assert_eq!(terms(json!({ "field": "title", "text": "the dreams" })), vec!["dream"]);
+        assert_eq!(terms(json!({ "field": "title", "text": "the dreams", "mode": "query" })), vec!["dream"]);
+        assert_eq!(terms(json!({ "field": "title", "text": "the dreams", "mode": "index" })), vec!["the", "dream"]);
+        assert_eq!(terms(json!({ "field": "title", "text": "the dreams", "mode": "phrase" })), vec!["the", "dream"]);
     }
 
-    Ok(ApiResult::new(Some(Value::Array(json_results))))
+    #[test]
+    fn test_analyze_errors() {
+        let error = |json: Value| analyze_json(&collection(), &json).err();
+
+        assert_eq!(error(json!({ "text": "dream" })), Some(ApiError::new(37, "You haven't provided field.")));
+        assert_eq!(error(json!({ "field": "title" })), Some(ApiError::new(38, "You haven't provided text.")));
+        assert_eq!(
+            error(json!({ "field": "title", "text": "dream", "mode": "fuzzy" })),
+            Some(ApiError::new(39, "You have provided invalid analysis mode."))
+        );
+        assert_eq!(
+            error(json!({ "field": "title", "text": "dream", "mode": null })),
+            Some(ApiError::new(39, "You have provided invalid analysis mode."))
+        );
+        assert_eq!(
+            error(json!({ "field": "body", "text": "dream" })),
+            Some(ApiError::new(23, "There is no field named `body`."))
+        );
+    }
 }
@@ -90,6 +90,9 @@ pub async fn create(req: &mut Request) -> Result<ApiResult, ApiError> {
             FieldValue::String(_, _) => {
                 FieldValue::String(value.as_str().unwrap().into(), None)
             }
+            FieldValue::Keyword(_) => {
+                FieldValue::Keyword(value.as_str().unwrap().into())
+            }
         };
 
         document.push(name, field_value);
@@ -128,7 +131,7 @@ pub async fn update(req: &mut Request) -> Result<ApiResult, ApiError> {
             FieldValue::Bool(value) => {
                 *value = field.1.as_bool().unwrap()
             }
-            FieldValue::String(value, _) => {
+            FieldValue::String(value, _) | FieldValue::Keyword(value) => {
                 *value = field.1.as_str().unwrap().to_string()
             }
         };
@@ -10,6 +10,8 @@ use crate::api_error::ApiError;
 pub mod state;
 pub mod collection;
 pub mod document;
+pub mod stop_words;
+pub mod synonym;
 
 pub async fn get_json_body(req: &mut Request) -> Result<Value, ApiError> {
     let data = String::from_utf8_lossy(req.payload().await.unwrap());
@@ -41,7 +43,7 @@ pub fn generate_fields_json(fields: &HashMap<String, FieldValue>) -> Value {
             FieldValue::Bool(value) => {
                 Value::Bool(*value)
             }
-            FieldValue::String(value, _) => {
+            FieldValue::String(value, _) | FieldValue::Keyword(value) => {
                 Value::String(value.into())
             }
         };
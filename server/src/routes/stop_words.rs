@@ -0,0 +1,41 @@
+use salvo::prelude::*;
+use serde_json::json;
+
+use crate::api_error::ApiError;
+use crate::api_result::ApiResult;
+use crate::routes::get_json_body;
+use crate::routes::collection::{get_collection, get_collection_id, get_strings};
+
+#[handler]
+pub async fn list(req: &mut Request) -> Result<ApiResult, ApiError> {
+    let id = get_collection_id(req).await?;
+    let collection = get_collection(id).await?;
+    let collection = collection.lock().unwrap();
+
+    Ok(ApiResult::new(Some(json!({ "stop_words": collection.stop_words }))))
+}
+
+/// Replaces the stop words of the collection with `{"stop_words": ["the", "of"]}`, they apply after the next commit.
+#[handler]
+pub async fn update(req: &mut Request) -> Result<ApiResult, ApiError> {
+    let id = get_collection_id(req).await?;
+    let collection = get_collection(id).await?;
+    let json = get_json_body(req).await?;
+    let stop_words = get_strings(&json["stop_words"]);
+
+    if stop_words.is_none() {
+        return Err(ApiError::new(35, "You have provided invalid stop words."));
+    }
+
+    collection.lock().unwrap().stop_words = stop_words.unwrap();
+    Ok(ApiResult::new(None))
+}
+
+#[handler]
+pub async fn clear(req: &mut Request) -> Result<ApiResult, ApiError> {
+    let id = get_collection_id(req).await?;
+    let collection = get_collection(id).await?;
+
+    collection.lock().unwrap().stop_words.clear();
+    Ok(ApiResult::new(None))
+}
@@ -0,0 +1,56 @@
+use std::str::FromStr;
+
+use salvo::prelude::*;
+use serde_json::json;
+
+use sightnet_core::synonym::Synonym;
+
+use crate::api_error::ApiError;
+use crate::api_result::ApiResult;
+use crate::routes::get_json_body;
+use crate::routes::collection::{get_collection, get_collection_id};
+
+#[handler]
+pub async fn list(req: &mut Request) -> Result<ApiResult, ApiError> {
+    let id = get_collection_id(req).await?;
+    let collection = get_collection(id).await?;
+    let collection = collection.lock().unwrap();
+    let synonyms: Vec<String> = collection.synonyms.iter().map(|x| x.to_string()).collect();
+
+    Ok(ApiResult::new(Some(json!({ "synonyms": synonyms }))))
+}
+
+/// Replaces the synonyms with `{"synonyms": ["tv, television", "ipod -> mp3 player"]}`.
+#[handler]
+pub async fn update(req: &mut Request) -> Result<ApiResult, ApiError> {
+    let id = get_collection_id(req).await?;
+    let collection = get_collection(id).await?;
+    let json = get_json_body(req).await?;
+    let synonyms = json["synonyms"].as_array();
+
+    if synonyms.is_none() {
+        return Err(ApiError::new(30, "You haven't provided synonyms."));
+    }
+
+    let synonyms = synonyms
+        .unwrap()
+        .iter()
+        .map(|x| {
+            x.as_str()
+                .and_then(|x| Synonym::from_str(x).ok())
+                .ok_or_else(|| ApiError::new(31, "You have provided invalid synonym."))
+        })
+        .collect::<Result<Vec<Synonym>, ApiError>>()?;
+
+    collection.lock().unwrap().synonyms = synonyms;
+    Ok(ApiResult::new(None))
+}
+
+#[handler]
+pub async fn clear(req: &mut Request) -> Result<ApiResult, ApiError> {
+    let id = get_collection_id(req).await?;
+    let collection = get_collection(id).await?;
+
+    collection.lock().unwrap().synonyms.clear();
+    Ok(ApiResult::new(None))
+}